@@ -0,0 +1,10 @@
+DROP TRIGGER IF EXISTS scraped_items_fts_update;
+DROP TRIGGER IF EXISTS scraped_items_fts_delete;
+DROP TRIGGER IF EXISTS scraped_items_fts_insert;
+DROP TABLE IF EXISTS scraped_items_fts;
+DROP TABLE IF EXISTS scrape_jobs;
+DROP TABLE IF EXISTS item_tags;
+DROP TABLE IF EXISTS crawl_state;
+DROP TABLE IF EXISTS crawls;
+DROP TABLE IF EXISTS scraped_variants;
+DROP TABLE IF EXISTS scraped_items;
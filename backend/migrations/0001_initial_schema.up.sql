@@ -0,0 +1,91 @@
+-- Schema as of the move to sqlx migrations. Statements use IF NOT EXISTS so
+-- this also applies cleanly to databases created before migrations existed.
+
+CREATE TABLE IF NOT EXISTS scraped_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    url TEXT NOT NULL UNIQUE,
+    content TEXT NOT NULL, -- Markdown
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    images_stripped INTEGER NOT NULL DEFAULT 0,
+    content_hash TEXT, -- SHA-256 of content
+    duplicate_of INTEGER, -- Earlier item with identical content
+    accept_language TEXT,
+    deleted_at TEXT, -- Set while the item is in the trash
+    plain_text TEXT,
+    readability INTEGER, -- NULL: readability never requested; 1: article stored; 0: readability found none
+    article TEXT,
+    html TEXT,
+    raw_html TEXT,
+    links TEXT -- JSON array
+);
+CREATE INDEX IF NOT EXISTS idx_scraped_items_content_hash ON scraped_items(content_hash);
+
+-- Per-viewport variants of an item (the item row itself holds the desktop rendering)
+CREATE TABLE IF NOT EXISTS scraped_variants (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_id INTEGER NOT NULL REFERENCES scraped_items(id) ON DELETE CASCADE,
+    viewport TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(item_id, viewport)
+);
+
+-- Crawl progress, so an interrupted crawl can be resumed where it stopped
+CREATE TABLE IF NOT EXISTS crawls (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    root_url TEXT NOT NULL,
+    max_depth INTEGER NOT NULL,
+    max_pages INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'running',
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+CREATE TABLE IF NOT EXISTS crawl_state (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    crawl_id INTEGER NOT NULL REFERENCES crawls(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    depth INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending', -- pending, done or failed
+    item_id INTEGER REFERENCES scraped_items(id) ON DELETE SET NULL,
+    UNIQUE(crawl_id, url)
+);
+
+-- Free-form labels on items; a tag row disappears with its item
+CREATE TABLE IF NOT EXISTS item_tags (
+    item_id INTEGER NOT NULL REFERENCES scraped_items(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (item_id, tag)
+);
+CREATE INDEX IF NOT EXISTS idx_item_tags_tag ON item_tags(tag);
+
+-- Queued `async` scrapes, worked through by the job workers
+CREATE TABLE IF NOT EXISTS scrape_jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    url TEXT NOT NULL,
+    request TEXT NOT NULL, -- The ScrapeRequest as JSON
+    headers TEXT, -- Forwarded client headers as JSON; cleared once the job finishes
+    status TEXT NOT NULL DEFAULT 'pending', -- pending, running, done or failed
+    item_id INTEGER REFERENCES scraped_items(id) ON DELETE SET NULL,
+    result TEXT, -- The ScrapeResponse as JSON, once done
+    error TEXT,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    run_after TEXT -- Retry backoff; NULL runs right away
+);
+
+-- Full-text index over items for GET /search, kept in sync by triggers
+CREATE VIRTUAL TABLE IF NOT EXISTS scraped_items_fts
+    USING fts5(url, content, content = 'scraped_items', content_rowid = 'id');
+CREATE TRIGGER IF NOT EXISTS scraped_items_fts_insert AFTER INSERT ON scraped_items BEGIN
+    INSERT INTO scraped_items_fts (rowid, url, content) VALUES (new.id, new.url, new.content);
+END;
+CREATE TRIGGER IF NOT EXISTS scraped_items_fts_delete AFTER DELETE ON scraped_items BEGIN
+    INSERT INTO scraped_items_fts (scraped_items_fts, rowid, url, content) VALUES ('delete', old.id, old.url, old.content);
+END;
+CREATE TRIGGER IF NOT EXISTS scraped_items_fts_update AFTER UPDATE OF url, content ON scraped_items BEGIN
+    INSERT INTO scraped_items_fts (scraped_items_fts, rowid, url, content) VALUES ('delete', old.id, old.url, old.content);
+    INSERT INTO scraped_items_fts (rowid, url, content) VALUES (new.id, new.url, new.content);
+END;
+-- Index the items already stored
+INSERT INTO scraped_items_fts (scraped_items_fts) VALUES ('rebuild');
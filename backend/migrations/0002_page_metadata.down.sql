@@ -0,0 +1,2 @@
+ALTER TABLE scraped_items DROP COLUMN description;
+ALTER TABLE scraped_items DROP COLUMN title;
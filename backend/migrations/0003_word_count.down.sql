@@ -0,0 +1 @@
+ALTER TABLE scraped_items DROP COLUMN word_count;
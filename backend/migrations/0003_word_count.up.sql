@@ -0,0 +1,2 @@
+-- Words in `content` once Markdown syntax is stripped; NULL for items stored before counting
+ALTER TABLE scraped_items ADD COLUMN word_count INTEGER;
@@ -0,0 +1,2 @@
+DROP TRIGGER IF EXISTS scraped_items_keep_revision;
+DROP TABLE IF EXISTS item_revisions;
@@ -0,0 +1,16 @@
+-- Earlier content of items that were re-scraped (force, another language, crawls),
+-- saved by the trigger below so changes to a page can be diffed
+CREATE TABLE item_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_id INTEGER NOT NULL REFERENCES scraped_items(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    content TEXT NOT NULL,
+    scraped_at TEXT, -- When the replaced content was scraped
+    replaced_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_item_revisions_item_id ON item_revisions(item_id);
+
+CREATE TRIGGER scraped_items_keep_revision BEFORE UPDATE OF content ON scraped_items
+WHEN old.content IS NOT new.content BEGIN
+    INSERT INTO item_revisions (item_id, url, content, scraped_at) VALUES (old.id, old.url, old.content, old.created_at);
+END;
@@ -0,0 +1,4 @@
+-- How an item was scraped: the scraper ("firecrawl") and the options sent to it
+-- as JSON, headers left out. NULL for items stored before they were recorded
+ALTER TABLE scraped_items ADD COLUMN source TEXT;
+ALTER TABLE scraped_items ADD COLUMN options TEXT;
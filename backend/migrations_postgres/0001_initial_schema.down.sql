@@ -0,0 +1,8 @@
+DROP TABLE IF EXISTS scrape_jobs;
+DROP TABLE IF EXISTS item_tags;
+DROP TABLE IF EXISTS crawl_state;
+DROP TABLE IF EXISTS crawls;
+DROP TABLE IF EXISTS scraped_variants;
+DROP TABLE IF EXISTS scraped_items;
+DROP FUNCTION IF EXISTS instr(text, text);
+DROP FUNCTION IF EXISTS datetime(text, text);
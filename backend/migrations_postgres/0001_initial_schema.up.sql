@@ -0,0 +1,91 @@
+-- The SQLite schema of backend/migrations in Postgres types. Timestamps stay
+-- UTC text in SQLite's 'YYYY-MM-DD HH:MM:SS' format, so both stores return
+-- the same values and the shared queries compare them the same way.
+
+-- SQLite's datetime('now'[, modifier]) and instr(), for the queries shared
+-- with it. Only 'now' is understood; modifiers are intervals like '-7 days'.
+CREATE FUNCTION datetime(value text, modifier text DEFAULT '0 seconds') RETURNS text AS $$
+    SELECT to_char((CURRENT_TIMESTAMP AT TIME ZONE 'UTC') + modifier::interval, 'YYYY-MM-DD HH24:MI:SS')
+    WHERE value = 'now'
+$$ LANGUAGE sql STABLE;
+
+CREATE FUNCTION instr(haystack text, needle text) RETURNS integer AS $$
+    SELECT strpos(haystack, needle)
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE TABLE scraped_items (
+    id BIGSERIAL PRIMARY KEY,
+    url TEXT NOT NULL UNIQUE,
+    content TEXT NOT NULL, -- Markdown
+    created_at TEXT DEFAULT datetime('now'),
+    images_stripped BOOLEAN NOT NULL DEFAULT FALSE,
+    content_hash TEXT, -- SHA-256 of content
+    duplicate_of BIGINT, -- Earlier item with identical content
+    accept_language TEXT,
+    deleted_at TEXT, -- Set while the item is in the trash
+    plain_text TEXT,
+    readability BOOLEAN, -- NULL: readability never requested; true: article stored; false: readability found none
+    article TEXT,
+    html TEXT,
+    raw_html TEXT,
+    links JSONB
+);
+CREATE INDEX idx_scraped_items_content_hash ON scraped_items(content_hash);
+
+-- Per-viewport variants of an item (the item row itself holds the desktop rendering)
+CREATE TABLE scraped_variants (
+    id BIGSERIAL PRIMARY KEY,
+    item_id BIGINT NOT NULL REFERENCES scraped_items(id) ON DELETE CASCADE,
+    viewport TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_at TEXT DEFAULT datetime('now'),
+    UNIQUE(item_id, viewport)
+);
+
+-- Crawl progress, so an interrupted crawl can be resumed where it stopped
+CREATE TABLE crawls (
+    id BIGSERIAL PRIMARY KEY,
+    root_url TEXT NOT NULL,
+    max_depth BIGINT NOT NULL,
+    max_pages BIGINT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'running',
+    created_at TEXT DEFAULT datetime('now'),
+    updated_at TEXT DEFAULT datetime('now')
+);
+CREATE TABLE crawl_state (
+    id BIGSERIAL PRIMARY KEY,
+    crawl_id BIGINT NOT NULL REFERENCES crawls(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    depth BIGINT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending', -- pending, done or failed
+    item_id BIGINT REFERENCES scraped_items(id) ON DELETE SET NULL,
+    UNIQUE(crawl_id, url)
+);
+
+-- Free-form labels on items; a tag row disappears with its item
+CREATE TABLE item_tags (
+    item_id BIGINT NOT NULL REFERENCES scraped_items(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (item_id, tag)
+);
+CREATE INDEX idx_item_tags_tag ON item_tags(tag);
+
+-- Queued `async` scrapes, worked through by the job workers
+CREATE TABLE scrape_jobs (
+    id BIGSERIAL PRIMARY KEY,
+    url TEXT NOT NULL,
+    request TEXT NOT NULL, -- The ScrapeRequest as JSON
+    headers TEXT, -- Forwarded client headers as JSON; cleared once the job finishes
+    status TEXT NOT NULL DEFAULT 'pending', -- pending, running, done or failed
+    item_id BIGINT REFERENCES scraped_items(id) ON DELETE SET NULL,
+    result TEXT, -- The ScrapeResponse as JSON, once done
+    error TEXT,
+    created_at TEXT DEFAULT datetime('now'),
+    updated_at TEXT DEFAULT datetime('now'),
+    attempts BIGINT NOT NULL DEFAULT 0,
+    run_after TEXT -- Retry backoff; NULL runs right away
+);
+
+-- Full-text index over items for GET /search; the query must use the same expression
+CREATE INDEX idx_scraped_items_search ON scraped_items
+    USING GIN (to_tsvector('simple', url || ' ' || content));
@@ -0,0 +1,3 @@
+-- Page metadata reported by Firecrawl; NULL when the page doesn't provide it
+ALTER TABLE scraped_items ADD COLUMN title TEXT;
+ALTER TABLE scraped_items ADD COLUMN description TEXT;
@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS scraped_items_keep_revision ON scraped_items;
+DROP FUNCTION IF EXISTS scraped_items_keep_revision();
+DROP TABLE IF EXISTS item_revisions;
@@ -0,0 +1,22 @@
+-- Earlier content of items that were re-scraped (force, another language, crawls),
+-- saved by the trigger below so changes to a page can be diffed
+CREATE TABLE item_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    item_id BIGINT NOT NULL REFERENCES scraped_items(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    content TEXT NOT NULL,
+    scraped_at TEXT, -- When the replaced content was scraped
+    replaced_at TEXT DEFAULT datetime('now')
+);
+CREATE INDEX idx_item_revisions_item_id ON item_revisions(item_id);
+
+CREATE FUNCTION scraped_items_keep_revision() RETURNS trigger AS $$
+BEGIN
+    INSERT INTO item_revisions (item_id, url, content, scraped_at) VALUES (OLD.id, OLD.url, OLD.content, OLD.created_at);
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER scraped_items_keep_revision BEFORE UPDATE OF content ON scraped_items
+FOR EACH ROW WHEN (OLD.content IS DISTINCT FROM NEW.content)
+EXECUTE FUNCTION scraped_items_keep_revision();
@@ -0,0 +1,2 @@
+ALTER TABLE scraped_items DROP COLUMN options;
+ALTER TABLE scraped_items DROP COLUMN source;
@@ -0,0 +1,81 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::ErrorResponse;
+
+/// Key that routes spending Firecrawl quota require as `Authorization: Bearer
+/// <key>`, from API_KEY. None leaves them open.
+pub type ApiKey = Option<Arc<str>>;
+
+/// Middleware for the protected routes: rejects requests without the right
+/// bearer key with 401. The header is removed once checked, so the key is
+/// never forwarded to Firecrawl along with other client headers.
+pub async fn require_api_key(State(api_key): State<ApiKey>, mut request: Request, next: Next) -> Response {
+    if let Some(key) = api_key {
+        let authorized = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|token| token == &*key);
+        if !authorized {
+            warn!("Rejected {} {}: invalid or missing API key", request.method(), request.uri().path());
+            let body = Json(ErrorResponse {
+                code: "INVALID_API_KEY",
+                message: "Invalid or missing API key".to_string(),
+            });
+            return (StatusCode::UNAUTHORIZED, body).into_response();
+        }
+        request.headers_mut().remove(header::AUTHORIZATION);
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::post, Router};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        // Echoes whether the Authorization header made it past the middleware
+        let handler = |request: Request| async move { request.headers().contains_key(header::AUTHORIZATION).to_string() };
+        Router::new()
+            .route("/scrape", post(handler))
+            .route_layer(middleware::from_fn_with_state(Some(Arc::from("secret")), require_api_key))
+    }
+
+    async fn send(authorization: Option<&str>) -> Response {
+        let mut request = Request::post("/scrape");
+        if let Some(value) = authorization {
+            request = request.header(header::AUTHORIZATION, value);
+        }
+        app().oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_missing_key_is_unauthorized() {
+        assert_eq!(send(None).await.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn a_wrong_key_is_unauthorized() {
+        assert_eq!(send(Some("Bearer wrong")).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(send(Some("secret")).await.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn the_right_key_passes_without_its_header() {
+        let response = send(Some("Bearer secret")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"false");
+    }
+}
@@ -0,0 +1,69 @@
+use axum::{extract::State, http::HeaderMap, Json};
+use futures::stream::{self, StreamExt};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tracing::{info, instrument, warn};
+
+use crate::{run_scrape, take_forwarded_language, telemetry, AppError, AppState, ScrapeRequest, ScrapeResponse};
+
+// URLs scraped at once; matches the DB pool size
+const BATCH_CONCURRENCY: usize = 5;
+const MAX_BATCH_URLS: usize = 100;
+
+#[derive(Deserialize, Debug)]
+pub struct BatchScrapeRequest {
+    urls: Vec<String>,
+}
+
+/// Scrapes several URLs like `POST /scrape` would, a few at a time. Repeated
+/// URLs are scraped once. Results are in request order, one per distinct URL;
+/// a failed URL gets an error entry instead of failing the batch.
+#[instrument(skip(state, headers))]
+pub async fn batch_scrape_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<BatchScrapeRequest>,
+) -> Result<Json<Vec<Result<ScrapeResponse, String>>>, AppError> {
+    let mut seen = HashSet::new();
+    let urls: Vec<String> = payload
+        .urls
+        .into_iter()
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty() && seen.insert(url.clone()))
+        .collect();
+    if urls.is_empty() {
+        return Err(AppError::BadRequest("urls must not be empty".to_string()));
+    }
+    if urls.len() > MAX_BATCH_URLS {
+        return Err(AppError::BadRequest(format!("At most {} URLs can be scraped in one batch", MAX_BATCH_URLS)));
+    }
+    info!("Received batch scrape request for {} URLs", urls.len());
+    metrics::counter!(telemetry::SCRAPES_TOTAL).increment(urls.len() as u64);
+
+    let mut forwarded = state.header_policy.forwarded(&headers);
+    let accept_language = take_forwarded_language(&mut forwarded);
+
+    let mut results: Vec<(usize, Result<ScrapeResponse, String>)> = stream::iter(urls.into_iter().enumerate())
+        .map(|(index, url)| {
+            let state = &state;
+            let forwarded = &forwarded;
+            let accept_language = accept_language.as_deref();
+            async move {
+                let request = ScrapeRequest { url, ..Default::default() };
+                let result = run_scrape(state, &request, accept_language, forwarded).await.map_err(|e| {
+                    warn!("Batch scrape of {} failed: {}", request.url, e);
+                    format!("{}: {}", request.url, e)
+                });
+                (index, result)
+            }
+        })
+        .buffer_unordered(BATCH_CONCURRENCY)
+        .collect()
+        .await;
+    results.sort_by_key(|(index, _)| *index);
+
+    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+    info!("Batch scrape finished: {} succeeded, {} failed", results.len() - failed, failed);
+    Ok(Json(results.into_iter().map(|(_, result)| result).collect()))
+}
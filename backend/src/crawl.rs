@@ -0,0 +1,523 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+    response::{IntoResponse, Response},
+    Json,
+};
+use firecrawl::scrape::{ScrapeFormats, ScrapeOptions};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::OwnedSemaphorePermit;
+use tracing::{error, info, instrument, warn};
+
+use crate::{
+    absolutize_urls, env_parse_or, find_duplicate, markdown, output, retry, stored_options, AppError, AppState,
+    FIRECRAWL_SOURCE,
+};
+
+// Hard server-side caps, overridable via CRAWL_MAX_DEPTH / CRAWL_MAX_PAGES.
+// Every crawl is clamped to these regardless of what the client asks for,
+// so a single request can't spend an unbounded number of Firecrawl credits.
+const DEFAULT_MAX_DEPTH_CAP: u32 = 5;
+const DEFAULT_MAX_PAGES_CAP: u32 = 100;
+
+// How often a progress subscriber re-reads the crawl's state
+const PROGRESS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// Used when the client doesn't specify limits
+const DEFAULT_MAX_DEPTH: u32 = 2;
+const DEFAULT_MAX_PAGES: u32 = 25;
+
+#[derive(Clone, Copy, Debug)]
+pub struct CrawlLimits {
+    pub max_depth: u32,
+    pub max_pages: u32,
+}
+
+impl CrawlLimits {
+    pub fn from_env() -> Self {
+        Self {
+            max_depth: env_parse_or("CRAWL_MAX_DEPTH", DEFAULT_MAX_DEPTH_CAP),
+            max_pages: env_parse_or("CRAWL_MAX_PAGES", DEFAULT_MAX_PAGES_CAP),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CrawlRequest {
+    url: String,
+    max_depth: Option<u32>,
+    max_pages: Option<u32>,
+    #[serde(default, rename = "async")]
+    run_async: bool, // Crawl in the background and return 202 right away
+}
+
+#[derive(Serialize)]
+pub struct CrawledPage {
+    id: i64,
+    url: String,
+    depth: u32,
+    cached: bool, // Already stored, so reused instead of scraped again
+}
+
+// Summary returned with 202 for a crawl running in the background
+#[derive(Serialize)]
+pub struct CrawlAccepted {
+    crawl_id: i64,
+    url: String,
+    max_depth: u32,
+    max_pages: u32,
+    status: &'static str,
+    events: String, // Where to follow its progress
+}
+
+#[derive(Serialize)]
+pub struct CrawlResponse {
+    crawl_id: i64,
+    url: String,
+    max_depth: u32, // Effective (clamped) depth that was actually crawled
+    max_pages: u32, // Effective (clamped) page cap that was actually applied
+    resumed: u32, // Pages already crawled by earlier runs of this crawl
+    remaining: u32, // Discovered pages left pending (e.g. when the page cap was reached)
+    pages: Vec<CrawledPage>, // Pages crawled by this run
+}
+
+// A row of the `crawls` table
+#[derive(sqlx::FromRow)]
+struct Crawl {
+    id: i64,
+    root_url: String,
+    #[sqlx(try_from = "i64")]
+    max_depth: u32,
+    #[sqlx(try_from = "i64")]
+    max_pages: u32,
+}
+
+// Clamps a requested value to the server cap, logging when the cap kicks in.
+fn clamp_to_cap(name: &str, requested: u32, cap: u32) -> u32 {
+    if requested > cap {
+        warn!("Requested {} {} exceeds server cap {}; clamping", name, requested, cap);
+        cap
+    } else {
+        requested
+    }
+}
+
+#[instrument(skip(state))]
+pub async fn crawl_handler(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<CrawlRequest>,
+) -> Result<Response, AppError> {
+    let limits = state.crawl_limits;
+    let max_depth = clamp_to_cap("max_depth", payload.max_depth.unwrap_or(DEFAULT_MAX_DEPTH), limits.max_depth);
+    let max_pages = clamp_to_cap("max_pages", payload.max_pages.unwrap_or(DEFAULT_MAX_PAGES), limits.max_pages);
+
+    let root = Url::parse(&payload.url)
+        .map_err(|e| AppError::BadRequest(format!("Invalid crawl URL {}: {}", payload.url, e)))?;
+
+    // Progress is persisted from the start so a failed crawl can be resumed
+    let crawl_id: i64 = sqlx::query_scalar(
+        "INSERT INTO crawls (root_url, max_depth, max_pages) VALUES ($1, $2, $3) RETURNING id",
+    )
+    .bind(root.as_str())
+    .bind(i64::from(max_depth))
+    .bind(i64::from(max_pages))
+    .fetch_one(&state.db)
+    .await?;
+    sqlx::query("INSERT INTO crawl_state (crawl_id, url, depth) VALUES ($1, $2, 0)")
+        .bind(crawl_id)
+        .bind(root.as_str())
+        .execute(&state.db)
+        .await?;
+    info!(
+        "Starting crawl {} of {} (max_depth: {}, max_pages: {})",
+        crawl_id, root, max_depth, max_pages
+    );
+
+    let crawl = Crawl {
+        id: crawl_id,
+        root_url: root.to_string(),
+        max_depth,
+        max_pages,
+    };
+    let running = ActiveCrawl::claim(&state, crawl_id)?;
+    if payload.run_async {
+        let body = Json(CrawlAccepted {
+            crawl_id,
+            url: crawl.root_url.clone(),
+            max_depth,
+            max_pages,
+            status: "running",
+            events: format!("/crawl/{}/events", crawl_id),
+        });
+        tokio::spawn(async move {
+            let state = running.state.clone();
+            if let Err(e) = run_crawl(&state, crawl, running).await {
+                error!("Background crawl {} failed: {}", crawl_id, e);
+            }
+        });
+        return Ok((StatusCode::ACCEPTED, body).into_response());
+    }
+    run_crawl(&state, crawl, running).await.map(|response| Json(response).into_response())
+}
+
+/// Continues a crawl from its persisted progress. Pages that failed in earlier
+/// runs are retried; pages already crawled are not fetched again.
+#[instrument(skip(state))]
+pub async fn resume_crawl_handler(
+    State(state): State<Arc<AppState>>,
+    Path(crawl_id): Path<i64>,
+) -> Result<Json<CrawlResponse>, AppError> {
+    let crawl: Crawl = sqlx::query_as("SELECT id, root_url, max_depth, max_pages FROM crawls WHERE id = $1")
+        .bind(crawl_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Crawl {} not found", crawl_id)))?;
+
+    let retried = sqlx::query("UPDATE crawl_state SET status = 'pending' WHERE crawl_id = $1 AND status = 'failed'")
+        .bind(crawl_id)
+        .execute(&state.db)
+        .await?
+        .rows_affected();
+    info!("Resuming crawl {} of {} ({} failed pages to retry)", crawl_id, crawl.root_url, retried);
+
+    let running = ActiveCrawl::claim(&state, crawl_id)?;
+    run_crawl(&state, crawl, running).await.map(Json)
+}
+
+// Marks a crawl as running for as long as it's held. Released on drop, so a
+// run cut short by a client disconnect doesn't block later resumes.
+struct ActiveCrawl {
+    state: Arc<AppState>,
+    crawl_id: i64,
+}
+
+impl ActiveCrawl {
+    // Guards against two runs of the same crawl racing over its pending pages
+    fn claim(state: &Arc<AppState>, crawl_id: i64) -> Result<Self, AppError> {
+        if !state.active_crawls.lock().unwrap().insert(crawl_id) {
+            return Err(AppError::Conflict(format!("Crawl {} is already running", crawl_id)));
+        }
+        Ok(Self {
+            state: state.clone(),
+            crawl_id,
+        })
+    }
+}
+
+impl Drop for ActiveCrawl {
+    fn drop(&mut self) {
+        self.state.active_crawls.lock().unwrap().remove(&self.crawl_id);
+    }
+}
+
+// Runs the crawl's pending pages and records how the run ended in `crawls.status`.
+// The claim is released when the run ends.
+async fn run_crawl(state: &AppState, crawl: Crawl, _running: ActiveCrawl) -> Result<CrawlResponse, AppError> {
+    set_crawl_status(state, crawl.id, "running").await?;
+    let result = crawl_pending(state, &crawl).await;
+
+    let status = match &result {
+        Ok(response) if response.remaining == 0 => "completed",
+        Ok(_) => "paused", // Page cap reached with pages still pending
+        Err(_) => "failed",
+    };
+    set_crawl_status(state, crawl.id, status).await?;
+    result
+}
+
+async fn set_crawl_status(state: &AppState, crawl_id: i64, status: &str) -> Result<(), AppError> {
+    sqlx::query("UPDATE crawls SET status = $1, updated_at = datetime('now') WHERE id = $2")
+        .bind(status)
+        .bind(crawl_id)
+        .execute(&state.db)
+        .await?;
+    Ok(())
+}
+
+// Works through pending pages breadth-first, recording each outcome in
+// `crawl_state` as it goes so an interrupted run loses at most one page.
+async fn crawl_pending(state: &AppState, crawl: &Crawl) -> Result<CrawlResponse, AppError> {
+    // Stored by crawl_handler after parsing, so this only fails on a corrupted row
+    let root = Url::parse(&crawl.root_url).map_err(|e| {
+        AppError::Internal(format!("Stored root URL {} of crawl {} can't be parsed: {}", crawl.root_url, crawl.id, e))
+    })?;
+    let resumed = count_pages(state, crawl.id, "done").await?;
+    let mut pages = Vec::new();
+
+    loop {
+        if resumed + pages.len() as u32 >= crawl.max_pages {
+            info!("Page cap of {} reached; stopping crawl {}", crawl.max_pages, crawl.id);
+            break;
+        }
+        let next: Option<(String, i64)> = sqlx::query_as(
+            "SELECT url, depth FROM crawl_state WHERE crawl_id = $1 AND status = 'pending' ORDER BY depth, id LIMIT 1",
+        )
+        .bind(crawl.id)
+        .fetch_optional(&state.db)
+        .await?;
+        let Some((url, depth)) = next else { break };
+        let depth = depth as u32;
+
+        // Pages already in the history are reused rather than spending Firecrawl
+        // credits again, as long as their links are stored or not needed.
+        // Pages in the trash stay there and aren't crawled.
+        let stored = stored_page(state, &url).await?;
+        let (id, links, cached) = match stored {
+            Some(StoredPage { trashed: true, .. }) => {
+                info!("Skipping {}: it is in the trash", url);
+                mark_page(state, crawl.id, &url, "skipped", None).await?;
+                continue;
+            }
+            Some(StoredPage { id, links: Some(links), .. }) => (id, links.0, true),
+            Some(StoredPage { id, links: None, .. }) if depth >= crawl.max_depth => (id, Vec::new(), true),
+            _ => {
+                let (markdown, links) = match scrape_page(state, &url).await {
+                    Ok(page) => page,
+                    Err(e) => {
+                        mark_page(state, crawl.id, &url, "failed", None).await?;
+                        // The root page must succeed; failures deeper in the site are skipped
+                        if depth == 0 {
+                            warn!("Crawl {} failed on its start page; retry with POST /crawl/{}/resume", crawl.id, crawl.id);
+                            return Err(e);
+                        }
+                        warn!("Skipping {} after scrape failure", url);
+                        continue;
+                    }
+                };
+                let id = store_page(state, &url, &markdown, &links).await?;
+                info!("Stored crawled page {} (ID: {}, depth: {})", url, id, depth);
+                (id, links, false)
+            }
+        };
+        if cached {
+            info!("Reusing stored page {} (ID: {}, depth: {})", url, id, depth);
+        }
+
+        if depth < crawl.max_depth {
+            for link in links {
+                let Ok(mut link_url) = Url::parse(&link) else { continue };
+                // Only follow links on the crawl's own host
+                if link_url.host_str() != root.host_str() {
+                    continue;
+                }
+                link_url.set_fragment(None);
+                // Pages this crawl has already seen are ignored via UNIQUE(crawl_id, url)
+                sqlx::query("INSERT INTO crawl_state (crawl_id, url, depth) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING")
+                    .bind(crawl.id)
+                    .bind(link_url.as_str())
+                    .bind(i64::from(depth) + 1)
+                    .execute(&state.db)
+                    .await?;
+            }
+        }
+        mark_page(state, crawl.id, &url, "done", Some(id)).await?;
+        pages.push(CrawledPage { id, url, depth, cached });
+    }
+
+    let remaining = count_pages(state, crawl.id, "pending").await?;
+    info!(
+        "Crawl {} of {} finished this run with {} pages ({} from earlier runs, {} remaining)",
+        crawl.id, crawl.root_url, pages.len(), resumed, remaining
+    );
+    Ok(CrawlResponse {
+        crawl_id: crawl.id,
+        url: crawl.root_url.clone(),
+        max_depth: crawl.max_depth,
+        max_pages: crawl.max_pages,
+        resumed,
+        remaining,
+        pages,
+    })
+}
+
+#[derive(Serialize, PartialEq)]
+struct CrawlProgress {
+    crawl_id: i64,
+    status: String, // running, paused, completed, failed, or interrupted (server stopped mid-run)
+    done: u32,
+    failed: u32,
+    pending: u32,
+}
+
+/// Server-sent events with a crawl's progress: a `progress` event whenever the
+/// counts change, ending after the crawl stops running. Concurrent subscribers
+/// are capped by `MAX_STREAM_SUBSCRIBERS`; over the cap this returns 503.
+#[instrument(skip(state))]
+pub async fn crawl_events_handler(
+    State(state): State<Arc<AppState>>,
+    Path(crawl_id): Path<i64>,
+) -> Result<Sse<impl futures::Stream<Item = Result<Event, Infallible>>>, AppError> {
+    let permit = state.stream_permits.clone().try_acquire_owned().map_err(|_| {
+        warn!(
+            "Stream subscriber limit of {} reached; rejecting progress subscriber for crawl {}",
+            state.max_stream_subscribers, crawl_id
+        );
+        AppError::ServiceUnavailable("Too many open progress streams; try again later".to_string())
+    })?;
+    // Fail fast for unknown crawls instead of opening a stream that ends immediately
+    crawl_progress(&state, crawl_id).await?;
+    info!("Progress subscriber attached to crawl {}", crawl_id);
+
+    // The permit lives in the stream's state, so it's released when the client goes away
+    let initial: (Arc<AppState>, OwnedSemaphorePermit, Option<CrawlProgress>, bool) = (state, permit, None, false);
+    let events = futures::stream::unfold(initial, move |(state, permit, last, finished)| async move {
+        if finished {
+            return None;
+        }
+        let mut first_poll = last.is_none();
+        loop {
+            if !first_poll {
+                tokio::time::sleep(PROGRESS_POLL_INTERVAL).await;
+            }
+            first_poll = false;
+
+            let progress = match crawl_progress(&state, crawl_id).await {
+                Ok(progress) => progress,
+                Err(_) => {
+                    let event = Event::default().event("error").data("Failed to read crawl progress");
+                    return Some((Ok(event), (state, permit, None, true)));
+                }
+            };
+            if last.as_ref() == Some(&progress) {
+                continue;
+            }
+            let finished = progress.status != "running";
+            let event = Event::default()
+                .event("progress")
+                .json_data(&progress)
+                .unwrap_or_else(|_| Event::default().event("error").data("Failed to encode crawl progress"));
+            return Some((Ok(event), (state, permit, Some(progress), finished)));
+        }
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+async fn crawl_progress(state: &AppState, crawl_id: i64) -> Result<CrawlProgress, AppError> {
+    let status: String = sqlx::query_scalar("SELECT status FROM crawls WHERE id = $1")
+        .bind(crawl_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Crawl {} not found", crawl_id)))?;
+    // A "running" crawl that no task is running was cut off by a server restart
+    let status = if status == "running" && !state.active_crawls.lock().unwrap().contains(&crawl_id) {
+        "interrupted".to_string()
+    } else {
+        status
+    };
+    Ok(CrawlProgress {
+        crawl_id,
+        status,
+        done: count_pages(state, crawl_id, "done").await?,
+        failed: count_pages(state, crawl_id, "failed").await?,
+        pending: count_pages(state, crawl_id, "pending").await?,
+    })
+}
+
+// The history item stored for a URL
+#[derive(sqlx::FromRow)]
+struct StoredPage {
+    id: i64,
+    trashed: bool,
+    links: Option<sqlx::types::Json<Vec<String>>>, // NULL when it was scraped without links
+}
+
+async fn stored_page(state: &AppState, url: &str) -> Result<Option<StoredPage>, AppError> {
+    let stored = sqlx::query_as("SELECT id, deleted_at IS NOT NULL AS trashed, links FROM scraped_items WHERE url = $1")
+        .bind(url)
+        .fetch_optional(&state.db)
+        .await?;
+    Ok(stored)
+}
+
+// Stores a crawled page in the history, links included so later crawls can
+// reuse it. A stored item for the URL is replaced like a forced scrape would:
+// everything derived from its old content is cleared and `created_at` is reset.
+// Items in the trash are never touched; the caller skips those URLs.
+async fn store_page(state: &AppState, url: &str, markdown: &str, links: &[String]) -> Result<i64, AppError> {
+    let content_hash = markdown::content_hash(markdown);
+    let duplicate_of = find_duplicate(&state.db, &content_hash, url).await?;
+    let id: i64 = sqlx::query_scalar(
+        "INSERT INTO scraped_items (url, content, content_hash, duplicate_of, links, word_count, source, options)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+         ON CONFLICT(url) DO UPDATE SET content = excluded.content,
+             content_hash = excluded.content_hash, duplicate_of = excluded.duplicate_of,
+             links = excluded.links, word_count = excluded.word_count,
+             source = excluded.source, options = excluded.options,
+             images_stripped = FALSE, accept_language = NULL, plain_text = NULL, html = NULL, raw_html = NULL,
+             readability = NULL, article = NULL, title = NULL, description = NULL,
+             created_at = datetime('now')
+         WHERE scraped_items.deleted_at IS NULL
+         RETURNING id",
+    )
+    .bind(url)
+    .bind(markdown)
+    .bind(&content_hash)
+    .bind(duplicate_of)
+    .bind(sqlx::types::Json(links))
+    .bind(i64::from(markdown::word_count(markdown)))
+    .bind(FIRECRAWL_SOURCE)
+    .bind(stored_options(&page_options()))
+    .fetch_one(&state.db)
+    .await?;
+    // Its variants were scraped from the old content
+    sqlx::query("DELETE FROM scraped_variants WHERE item_id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await?;
+    if let Some(dir) = &state.output_dir {
+        output::write_markdown_file(dir, url, id, markdown).await;
+    }
+    Ok(id)
+}
+
+// `status` is pending, done, failed or skipped (the page is in the trash)
+async fn mark_page(state: &AppState, crawl_id: i64, url: &str, status: &str, item_id: Option<i64>) -> Result<(), AppError> {
+    sqlx::query("UPDATE crawl_state SET status = $1, item_id = $2 WHERE crawl_id = $3 AND url = $4")
+        .bind(status)
+        .bind(item_id)
+        .bind(crawl_id)
+        .bind(url)
+        .execute(&state.db)
+        .await?;
+    Ok(())
+}
+
+async fn count_pages(state: &AppState, crawl_id: i64, status: &str) -> Result<u32, AppError> {
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM crawl_state WHERE crawl_id = $1 AND status = $2")
+        .bind(crawl_id)
+        .bind(status)
+        .fetch_one(&state.db)
+        .await?;
+    Ok(count as u32)
+}
+
+// What every crawled page is scraped with
+fn page_options() -> ScrapeOptions {
+    ScrapeOptions {
+        formats: Some(vec![ScrapeFormats::Markdown, ScrapeFormats::Links]),
+        ..Default::default()
+    }
+}
+
+// Scrapes one page, returning its Markdown and the links found on it.
+async fn scrape_page(state: &AppState, url: &str) -> Result<(String, Vec<String>), AppError> {
+    let scrape_options = page_options();
+
+    let firecrawl_app = state.firecrawl()?;
+    let document = retry::with_retries(state.firecrawl_retry, url, || {
+        firecrawl_app.scrape_url(url, Some(scrape_options.clone()))
+    })
+    .await
+    .inspect_err(|e| warn!("Firecrawl failed to scrape {}: {}", url, e))?;
+
+    let markdown = document
+        .markdown
+        .ok_or_else(|| AppError::Internal("Firecrawl did not return Markdown content".to_string()))?;
+    Ok((absolutize_urls(&markdown, url), document.links.unwrap_or_default()))
+}
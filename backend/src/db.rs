@@ -0,0 +1,174 @@
+// The database behind the history: SQLite by default, Postgres when built with
+// the `postgres` feature. Queries are shared between the two, so they stick to
+// SQL both understand: `$N` placeholders, ON CONFLICT, RETURNING, and the
+// datetime('now') and instr() that the Postgres schema defines to match SQLite's.
+
+use sqlx::migrate::Migrator;
+use sqlx::pool::PoolOptions;
+use std::time::Duration;
+use tracing::info;
+
+use crate::env_parse_or;
+
+#[cfg(not(feature = "postgres"))]
+pub type Db = sqlx::Sqlite;
+#[cfg(feature = "postgres")]
+pub type Db = sqlx::Postgres;
+
+pub type DbPool = sqlx::Pool<Db>;
+
+// Schema changes are embedded at compile time: backend/migrations for SQLite,
+// backend/migrations_postgres (the same steps in Postgres types) for Postgres
+#[cfg(not(feature = "postgres"))]
+static MIGRATOR: Migrator = sqlx::migrate!();
+#[cfg(feature = "postgres")]
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations_postgres");
+
+fn is_postgres_url(database_url: &str) -> bool {
+    database_url.starts_with("postgres:") || database_url.starts_with("postgresql:")
+}
+
+// Pool size and acquire timeout, from DB_MAX_CONNECTIONS / DB_CONNECT_TIMEOUT_SECS
+fn pool_options() -> PoolOptions<Db> {
+    let max_connections = env_parse_or("DB_MAX_CONNECTIONS", 5u32).max(1);
+    let acquire_timeout = Duration::from_secs(env_parse_or("DB_CONNECT_TIMEOUT_SECS", 30u64));
+    info!(
+        "Database pool: up to {} connections, {}s to acquire one",
+        max_connections,
+        acquire_timeout.as_secs()
+    );
+    PoolOptions::new()
+        .max_connections(max_connections)
+        .acquire_timeout(acquire_timeout)
+}
+
+/// Opens the SQLite database at `database_url` and runs the migrations. A
+/// missing file is created unless DB_CREATE_IF_MISSING is false.
+#[cfg(not(feature = "postgres"))]
+pub async fn connect(database_url: &str) -> Result<DbPool, String> {
+    use sqlx::sqlite::SqliteConnectOptions;
+    use std::str::FromStr;
+
+    if is_postgres_url(database_url) {
+        return Err("DATABASE_URL points at Postgres; build the backend with `--features postgres` to use it".to_string());
+    }
+
+    // Whether a missing database file is created, regardless of the URL's `mode=`
+    let db_create_if_missing = env_parse_or("DB_CREATE_IF_MISSING", true);
+    let connect_options = SqliteConnectOptions::from_str(database_url)
+        .map_err(|e| format!("DATABASE_URL is not a valid SQLite URL: {}", e))?
+        .create_if_missing(db_create_if_missing);
+    let db_path = connect_options.clone().get_filename();
+    // In-memory databases get a generated `file:sqlx-in-memory-*` name
+    let is_file = !db_path.to_string_lossy().starts_with("file:");
+    if is_file && !db_path.exists() {
+        if !db_create_if_missing {
+            let message = format!(
+                "Database file {} does not exist and DB_CREATE_IF_MISSING is false; create it or check DATABASE_URL",
+                db_path.display()
+            );
+            return Err(message);
+        }
+        info!("Database file {} does not exist; creating a new database", db_path.display());
+    }
+
+    let pool = pool_options()
+        .connect_with(connect_options)
+        .await
+        .map_err(|e| format!("Failed to create database pool: {}", e))?;
+
+    info!("Running database migrations...");
+    // One connection for the whole upgrade, so the pool's others only ever see the new schema
+    let mut conn = pool.acquire().await.map_err(migration_error)?;
+    legacy::upgrade_legacy_schema(&mut conn).await.map_err(migration_error)?;
+    MIGRATOR.run(&mut *conn).await.map_err(migration_error)?;
+    Ok(pool)
+}
+
+/// Connects to the Postgres database at `database_url` and runs the migrations.
+#[cfg(feature = "postgres")]
+pub async fn connect(database_url: &str) -> Result<DbPool, String> {
+    if !is_postgres_url(database_url) {
+        return Err("The backend was built with the `postgres` feature; DATABASE_URL must be a postgres:// URL".to_string());
+    }
+    let pool = pool_options()
+        .connect(database_url)
+        .await
+        .map_err(|e| format!("Failed to create database pool: {}", e))?;
+
+    info!("Running database migrations...");
+    MIGRATOR.run(&pool).await.map_err(migration_error)?;
+    Ok(pool)
+}
+
+fn migration_error(e: impl std::fmt::Display) -> String {
+    format!("Failed to run database migrations: {}", e)
+}
+
+// SQLite databases from before the move to migrations. Postgres support came
+// later, so its databases never need this.
+#[cfg(not(feature = "postgres"))]
+mod legacy {
+    use sqlx::sqlite::SqliteConnection;
+    use tracing::info;
+
+    // Databases created before migrations grew their columns one ALTER TABLE at a
+    // time. Brings them up to the schema of the first migration, whose
+    // CREATE ... IF NOT EXISTS statements then fill in everything else.
+    pub async fn upgrade_legacy_schema(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+        let (is_legacy,): (bool,) = sqlx::query_as(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = 'scraped_items')
+                AND NOT EXISTS (SELECT 1 FROM sqlite_master WHERE name = '_sqlx_migrations')",
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        if !is_legacy {
+            return Ok(());
+        }
+
+        info!("Upgrading a database created before migrations");
+        let item_columns = [
+            ("images_stripped", "INTEGER NOT NULL DEFAULT 0"),
+            ("content_hash", "TEXT"),
+            ("duplicate_of", "INTEGER"),
+            ("accept_language", "TEXT"),
+            ("deleted_at", "TEXT"),
+            ("plain_text", "TEXT"),
+            ("readability", "INTEGER"),
+            ("article", "TEXT"),
+            ("html", "TEXT"),
+            ("raw_html", "TEXT"),
+            ("links", "TEXT"),
+        ];
+        for (column, definition) in item_columns {
+            add_column_if_missing(conn, "scraped_items", column, definition).await?;
+        }
+        let (has_jobs,): (bool,) = sqlx::query_as("SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = 'scrape_jobs')")
+            .fetch_one(&mut *conn)
+            .await?;
+        if has_jobs {
+            add_column_if_missing(conn, "scrape_jobs", "attempts", "INTEGER NOT NULL DEFAULT 0").await?;
+            add_column_if_missing(conn, "scrape_jobs", "run_after", "TEXT").await?;
+        }
+        Ok(())
+    }
+
+    // Adds a column to an existing table unless it is already present.
+    async fn add_column_if_missing(
+        conn: &mut SqliteConnection,
+        table: &str,
+        column: &str,
+        definition: &str,
+    ) -> Result<(), sqlx::Error> {
+        let columns: Vec<(String,)> = sqlx::query_as(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .fetch_all(&mut *conn)
+            .await?;
+        if !columns.iter().any(|(name,)| name == column) {
+            info!("Adding column {}.{}", table, column);
+            sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+                .execute(&mut *conn)
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use axum::{extract::State, Json};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{info, instrument};
+
+use crate::{db::DbPool, markdown, AppError, AppState};
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct DuplicateItem {
+    id: i64,
+    url: String,
+    created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct DuplicateGroup {
+    hash: String,
+    items: Vec<DuplicateItem>, // Oldest first
+}
+
+/// Groups history items with identical content, for cleaning up redundant
+/// scrapes. Only groups with more than one item are returned; trashed items
+/// are left out.
+#[instrument(skip(state))]
+pub async fn duplicates_handler(State(state): State<Arc<AppState>>) -> Result<Json<Vec<DuplicateGroup>>, AppError> {
+    backfill_content_hashes(&state.db).await?;
+
+    let rows: Vec<(String, i64, String, String)> = sqlx::query_as(
+        "SELECT content_hash, id, url, created_at FROM scraped_items
+         WHERE deleted_at IS NULL AND content_hash IN (
+             SELECT content_hash FROM scraped_items
+             WHERE deleted_at IS NULL AND content_hash IS NOT NULL
+             GROUP BY content_hash HAVING COUNT(*) > 1
+         )
+         ORDER BY content_hash, id",
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let mut groups: Vec<DuplicateGroup> = Vec::new();
+    for (hash, id, url, created_at) in rows {
+        let item = DuplicateItem { id, url, created_at };
+        match groups.last_mut() {
+            Some(group) if group.hash == hash => group.items.push(item),
+            _ => groups.push(DuplicateGroup { hash, items: vec![item] }),
+        }
+    }
+    info!("Found {} groups of duplicate items", groups.len());
+    Ok(Json(groups))
+}
+
+// Hashes items stored before content hashing existed, so they can be grouped
+async fn backfill_content_hashes(db: &DbPool) -> Result<(), AppError> {
+    let unhashed: Vec<(i64, String)> =
+        sqlx::query_as("SELECT id, content FROM scraped_items WHERE content_hash IS NULL AND deleted_at IS NULL")
+            .fetch_all(db)
+            .await?;
+    if unhashed.is_empty() {
+        return Ok(());
+    }
+
+    let mut tx = db.begin().await?;
+    for (id, content) in &unhashed {
+        sqlx::query("UPDATE scraped_items SET content_hash = $1 WHERE id = $2")
+            .bind(markdown::content_hash(content))
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    info!("Backfilled content hashes for {} items", unhashed.len());
+    Ok(())
+}
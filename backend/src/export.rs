@@ -0,0 +1,108 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use futures::StreamExt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{error, info, instrument};
+
+use crate::{db::DbPool, AppState, ScrapedItem, ITEM_COLUMNS};
+
+// Buffered output is flushed to the client once it reaches this size
+const FLUSH_BYTES: usize = 64 * 1024;
+
+// With no row ready for this long, whatever is buffered is flushed, or a blank
+// line is sent, so proxies don't treat the export as idle and drop it
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(10);
+
+// Chunks waiting to be written; bounds memory when the client reads slowly
+const CHANNEL_CAPACITY: usize = 8;
+
+type Chunk = Result<Bytes, std::io::Error>;
+
+/// Streams the whole history as newline-delimited JSON, one `ScrapedItem` per line.
+/// Rows are read from a `sqlx` row stream and written in bounded chunks, so memory
+/// use stays flat no matter how large the table is.
+#[instrument(skip(state))]
+pub async fn export_handler(State(state): State<Arc<AppState>>) -> Response {
+    info!("Starting streamed history export");
+    let (tx, mut rx) = mpsc::channel::<Chunk>(CHANNEL_CAPACITY);
+    tokio::spawn(stream_items(state.db.clone(), tx));
+
+    let chunks = futures::stream::poll_fn(move |cx| rx.poll_recv(cx));
+    (
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"history.ndjson\""),
+            (header::CACHE_CONTROL, "no-cache"),
+            // Tells nginx-style proxies not to buffer the whole response
+            (header::HeaderName::from_static("x-accel-buffering"), "no"),
+        ],
+        Body::from_stream(chunks),
+    )
+        .into_response()
+}
+
+// Reads rows one at a time and feeds NDJSON chunks to the response body.
+// Stops early when the client disconnects (the receiver is dropped).
+async fn stream_items(db: DbPool, tx: mpsc::Sender<Chunk>) {
+    let sql = format!("SELECT {} FROM scraped_items WHERE deleted_at IS NULL ORDER BY created_at DESC", ITEM_COLUMNS);
+    let mut rows = sqlx::query_as::<_, ScrapedItem>(&sql).fetch(&db);
+    let mut buffer = Vec::with_capacity(FLUSH_BYTES);
+    let mut exported = 0usize;
+
+    loop {
+        let row = match tokio::time::timeout(KEEP_ALIVE_INTERVAL, rows.next()).await {
+            Ok(Some(row)) => row,
+            Ok(None) => break,
+            Err(_) => {
+                // Slow query or slow disk: send what we have, or a heartbeat line
+                if buffer.is_empty() {
+                    buffer.push(b'\n');
+                }
+                if send(&tx, &mut buffer).await.is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+
+        let item = match row {
+            Ok(item) => item,
+            Err(e) => {
+                error!("History export failed after {} items: {}", exported, e);
+                // Abort the body so the client sees a truncated transfer, not a clean end
+                let _ = tx.send(Err(std::io::Error::other(e))).await;
+                return;
+            }
+        };
+
+        if let Err(e) = serde_json::to_writer(&mut buffer, &item) {
+            error!("Failed to serialize item {} for export: {}", item.id, e);
+            continue;
+        }
+        buffer.push(b'\n');
+        exported += 1;
+
+        if buffer.len() >= FLUSH_BYTES && send(&tx, &mut buffer).await.is_err() {
+            return;
+        }
+    }
+
+    if !buffer.is_empty() && send(&tx, &mut buffer).await.is_err() {
+        return;
+    }
+    info!("History export finished with {} items", exported);
+}
+
+// Hands the buffered bytes to the response body, leaving the buffer empty.
+async fn send(tx: &mpsc::Sender<Chunk>, buffer: &mut Vec<u8>) -> Result<(), ()> {
+    let chunk = Bytes::from(std::mem::replace(buffer, Vec::with_capacity(FLUSH_BYTES)));
+    tx.send(Ok(chunk)).await.map_err(|_| {
+        info!("Client disconnected during history export");
+    })
+}
@@ -0,0 +1,106 @@
+use axum::http::HeaderMap;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fmt;
+use tracing::warn;
+
+use crate::env_parse_or;
+
+// Never forwarded: they describe the connection to this server, not the page fetch
+const HOP_BY_HOP_HEADERS: &[&str] = &[
+    "host",
+    "connection",
+    "keep-alive",
+    "transfer-encoding",
+    "te",
+    "trailer",
+    "upgrade",
+    "content-length",
+    "content-type",
+    "accept-encoding",
+];
+
+// Credentials are blocked unless explicitly enabled
+const DEFAULT_BLOCKED_HEADERS: &[&str] = &["cookie", "proxy-authorization"];
+
+/// Which client request headers are passed on when the server fetches a page
+/// on the client's behalf. A header is forwarded only when it's on the
+/// allowlist and not on the blocklist; the blocklist always wins.
+pub struct HeaderPolicy {
+    allow: Vec<String>,
+    block: HashSet<String>,
+}
+
+impl HeaderPolicy {
+    /// Reads `FORWARD_HEADERS` (comma-separated allowlist, empty by default),
+    /// `BLOCK_HEADERS` (extra headers to never forward) and
+    /// `FORWARD_AUTHORIZATION` (default false).
+    pub fn from_env() -> Self {
+        let mut block: HashSet<String> = HOP_BY_HOP_HEADERS
+            .iter()
+            .chain(DEFAULT_BLOCKED_HEADERS)
+            .map(|name| name.to_string())
+            .chain(header_list("BLOCK_HEADERS"))
+            .collect();
+        if !env_parse_or("FORWARD_AUTHORIZATION", false) {
+            block.insert("authorization".to_string());
+        }
+
+        let allow: Vec<String> = header_list("FORWARD_HEADERS").collect();
+        for name in allow.iter().filter(|name| block.contains(*name)) {
+            warn!("Header {} is in FORWARD_HEADERS but blocked; it will not be forwarded", name);
+        }
+        Self { allow, block }
+    }
+
+    pub fn allowed(&self) -> impl Iterator<Item = &str> {
+        self.allow.iter().filter(|name| !self.block.contains(*name)).map(String::as_str)
+    }
+
+    /// Picks the headers to forward out of a client request. Keys are lowercase;
+    /// values that aren't visible ASCII are dropped.
+    pub fn forwarded(&self, headers: &HeaderMap) -> HashMap<String, String> {
+        self.allowed()
+            .filter_map(|name| {
+                let value = headers.get(name)?.to_str().ok()?;
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect()
+    }
+}
+
+// Lowercased, trimmed header names from a comma-separated env var
+fn header_list(key: &str) -> impl Iterator<Item = String> {
+    env::var(key)
+        .unwrap_or_default()
+        .split(',')
+        .map(|name| name.trim().to_ascii_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+/// A credential from a request body. Its `Debug` output is redacted, so it
+/// stays out of logs and tracing spans.
+#[derive(Deserialize, Clone)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+/// `Authorization` header value for HTTP basic auth (RFC 7617).
+pub fn basic_auth(username: &str, password: &str) -> String {
+    format!("Basic {}", BASE64_STANDARD.encode(format!("{}:{}", username, password)))
+}
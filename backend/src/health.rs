@@ -0,0 +1,73 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use firecrawl::scrape::{ScrapeFormats, ScrapeOptions};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{instrument, warn};
+
+use crate::AppState;
+
+// Small, stable page scraped by the deep check
+const PROBE_URL: &str = "https://example.com";
+
+#[derive(Deserialize, Debug)]
+pub struct HealthQuery {
+    #[serde(default)]
+    deep: bool, // Also scrape a page through Firecrawl (uses API quota)
+}
+
+#[derive(Serialize)]
+pub struct HealthResponse {
+    status: &'static str,
+    db: bool,
+    firecrawl: bool, // Configured, or with `deep` actually answering
+}
+
+/// Liveness/readiness probe: 200 while the database answers, 503 otherwise.
+/// Firecrawl's state is reported but doesn't fail the probe.
+#[instrument(skip(state))]
+pub async fn health_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HealthQuery>,
+) -> (StatusCode, Json<HealthResponse>) {
+    let db = match sqlx::query("SELECT 1").execute(&state.db).await {
+        Ok(_) => true,
+        Err(e) => {
+            warn!("Health check: database unavailable: {}", e);
+            false
+        }
+    };
+    let firecrawl = if query.deep { probe_firecrawl(&state).await } else { state.firecrawl_app.is_some() };
+
+    let (status_code, status) = if db {
+        (StatusCode::OK, "ok")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    };
+    (status_code, Json(HealthResponse { status, db, firecrawl }))
+}
+
+// One scrape of PROBE_URL within the usual timeout, without retries
+async fn probe_firecrawl(state: &AppState) -> bool {
+    let Some(firecrawl_app) = &state.firecrawl_app else {
+        return false;
+    };
+    let options = ScrapeOptions {
+        formats: Some(vec![ScrapeFormats::Markdown]),
+        ..Default::default()
+    };
+    match tokio::time::timeout(state.firecrawl_retry.timeout, firecrawl_app.scrape_url(PROBE_URL, Some(options))).await {
+        Ok(Ok(_)) => true,
+        Ok(Err(e)) => {
+            warn!("Health check: Firecrawl failed: {}", e);
+            false
+        }
+        Err(_) => {
+            warn!("Health check: Firecrawl timed out");
+            false
+        }
+    }
+}
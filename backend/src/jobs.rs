@@ -0,0 +1,259 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{error, info, instrument, warn};
+
+use crate::{db::DbPool, env_parse_or, retry, run_scrape, AppError, AppState, ScrapeRequest};
+
+// Base delay before retrying a job after a transient failure; doubles per attempt
+const RETRY_BASE_DELAY_SECS: u64 = 10;
+
+// How long shutdown waits for jobs in progress before leaving them to be requeued
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(30);
+
+/// Job worker settings, overridable via SCRAPE_WORKERS / JOB_POLL_SECS / JOB_MAX_ATTEMPTS.
+#[derive(Clone, Copy, Debug)]
+pub struct JobSettings {
+    pub workers: usize,
+    pub poll_interval: Duration, // Idle workers re-check the queue this often, in case a wake-up was missed
+    pub max_attempts: u32, // Tries per job before a transient failure is final
+}
+
+impl JobSettings {
+    pub fn from_env() -> Self {
+        Self {
+            workers: env_parse_or("SCRAPE_WORKERS", 1usize).max(1),
+            poll_interval: Duration::from_secs(env_parse_or("JOB_POLL_SECS", 5u64).max(1)),
+            max_attempts: env_parse_or("JOB_MAX_ATTEMPTS", 3u32).max(1),
+        }
+    }
+}
+
+/// Running job workers; call [`JobWorkers::shutdown`] to stop them.
+pub struct JobWorkers {
+    stop: watch::Sender<bool>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl JobWorkers {
+    /// Stops claiming new jobs and waits (up to a grace period) for jobs in
+    /// progress. Jobs still running after that are requeued on the next start.
+    pub async fn shutdown(self) {
+        let _ = self.stop.send(true);
+        let all_stopped = futures::future::join_all(self.handles);
+        if tokio::time::timeout(SHUTDOWN_GRACE, all_stopped).await.is_err() {
+            warn!("Scrape jobs still running after {:?}; they'll be requeued on restart", SHUTDOWN_GRACE);
+        } else {
+            info!("Scrape job workers stopped");
+        }
+    }
+}
+
+/// A queued `async` scrape, as returned by `GET /jobs/:id`.
+#[derive(Serialize, sqlx::FromRow)]
+pub struct ScrapeJob {
+    id: i64,
+    url: String,
+    status: String, // pending, running, done or failed
+    attempts: i64,
+    item_id: Option<i64>,
+    error: Option<String>,
+    created_at: String,
+    updated_at: String,
+    #[serde(skip)]
+    #[sqlx(rename = "result")]
+    result_json: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[sqlx(skip)]
+    result: Option<serde_json::Value>, // The scrape response, once done
+}
+
+// A job claimed by a worker
+#[derive(sqlx::FromRow)]
+struct ClaimedJob {
+    id: i64,
+    #[sqlx(try_from = "i64")]
+    attempts: u32,
+    request: String,
+    headers: Option<String>,
+}
+
+/// Stores a validated scrape request as a pending job and wakes a worker.
+pub async fn enqueue(
+    state: &AppState,
+    payload: &ScrapeRequest,
+    forwarded: &HashMap<String, String>,
+) -> Result<i64, AppError> {
+    let request = serde_json::to_string(payload)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize scrape job: {}", e)))?;
+    let headers = (!forwarded.is_empty())
+        .then(|| serde_json::to_string(forwarded))
+        .transpose()
+        .map_err(|e| AppError::Internal(format!("Failed to serialize scrape job headers: {}", e)))?;
+
+    let job_id: i64 = sqlx::query_scalar("INSERT INTO scrape_jobs (url, request, headers) VALUES ($1, $2, $3) RETURNING id")
+        .bind(&payload.url)
+        .bind(&request)
+        .bind(&headers)
+        .fetch_one(&state.db)
+        .await?;
+    state.job_notify.notify_one();
+    info!("Queued scrape job {} for {}", job_id, payload.url);
+    Ok(job_id)
+}
+
+/// Reports a job's status, with the scrape response once it's done.
+#[instrument(skip(state))]
+pub async fn get_job_handler(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<i64>,
+) -> Result<Json<ScrapeJob>, AppError> {
+    let mut job = sqlx::query_as::<_, ScrapeJob>(
+        "SELECT id, url, status, attempts, item_id, error, created_at, updated_at, result FROM scrape_jobs WHERE id = $1",
+    )
+    .bind(job_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Job {} not found", job_id)))?;
+
+    job.result = job
+        .result_json
+        .take()
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|e| AppError::Internal(format!("Stored result of job {} is not valid JSON: {}", job_id, e)))?;
+    Ok(Json(job))
+}
+
+/// Requeues jobs left running by a previous process, then starts the worker
+/// tasks, which work through the queue oldest first.
+pub async fn start_workers(state: Arc<AppState>, settings: JobSettings) -> JobWorkers {
+    match sqlx::query("UPDATE scrape_jobs SET status = 'pending', updated_at = datetime('now') WHERE status = 'running'")
+        .execute(&state.db)
+        .await
+    {
+        Ok(done) if done.rows_affected() > 0 => {
+            info!("Requeued {} interrupted scrape jobs", done.rows_affected())
+        }
+        Ok(_) => {}
+        Err(e) => error!("Failed to requeue interrupted scrape jobs: {}", e),
+    }
+
+    let (stop, stop_rx) = watch::channel(false);
+    let handles = (0..settings.workers)
+        .map(|_| tokio::spawn(work(state.clone(), settings, stop_rx.clone())))
+        .collect();
+    JobWorkers { stop, handles }
+}
+
+async fn work(state: Arc<AppState>, settings: JobSettings, mut stop: watch::Receiver<bool>) {
+    while !*stop.borrow() {
+        let idle = match claim_job(&state.db).await {
+            Ok(Some(job)) => {
+                run_job(&state, job, settings.max_attempts).await;
+                false
+            }
+            Ok(None) => true,
+            Err(e) => {
+                error!("Failed to claim a scrape job: {}", e);
+                true
+            }
+        };
+        if idle {
+            tokio::select! {
+                _ = state.job_notify.notified() => {}
+                _ = tokio::time::sleep(settings.poll_interval) => {}
+                _ = stop.changed() => {}
+            }
+        }
+    }
+}
+
+// Marks the oldest runnable job as running and returns it. A single statement
+// that re-checks the status, so two workers can't claim the same job (on
+// Postgres, the loser of the race finds the row already running and gets None).
+async fn claim_job(db: &DbPool) -> Result<Option<ClaimedJob>, sqlx::Error> {
+    sqlx::query_as(
+        "UPDATE scrape_jobs SET status = 'running', attempts = attempts + 1, updated_at = datetime('now')
+         WHERE id = (
+             SELECT id FROM scrape_jobs
+             WHERE status = 'pending' AND (run_after IS NULL OR run_after <= datetime('now'))
+             ORDER BY id LIMIT 1
+         ) AND status = 'pending'
+         RETURNING id, attempts, request, headers",
+    )
+    .fetch_optional(db)
+    .await
+}
+
+// Failures worth another attempt: Firecrawl hiccups and timeouts, database contention
+fn is_transient(error: &AppError) -> bool {
+    match error {
+        AppError::Firecrawl(e) => retry::is_transient(e),
+        AppError::Timeout(_) | AppError::Sqlx(_) => true,
+        _ => false,
+    }
+}
+
+async fn run_job(state: &AppState, job: ClaimedJob, max_attempts: u32) {
+    info!("Running scrape job {} (attempt {} of {})", job.id, job.attempts, max_attempts);
+    let outcome = async {
+        let request: ScrapeRequest = serde_json::from_str(&job.request)
+            .map_err(|e| AppError::Internal(format!("Stored scrape request is not valid: {}", e)))?;
+        let forwarded: HashMap<String, String> = match &job.headers {
+            Some(headers) => serde_json::from_str(headers)
+                .map_err(|e| AppError::Internal(format!("Stored scrape headers are not valid: {}", e)))?,
+            None => HashMap::new(),
+        };
+        let response = run_scrape(state, &request, request.accept_language.as_deref(), &forwarded).await?;
+        let result = serde_json::to_string(&response)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize scrape result: {}", e)))?;
+        Ok::<_, AppError>((response.id, result))
+    }
+    .await;
+
+    // Forwarded headers can hold credentials, so they're dropped once the job is over
+    // (kept while a retry is pending)
+    let update = match outcome {
+        Ok((item_id, result)) => {
+            info!("Scrape job {} done", job.id);
+            sqlx::query(
+                "UPDATE scrape_jobs SET status = 'done', item_id = $1, result = $2, error = NULL, headers = NULL,
+                     updated_at = datetime('now') WHERE id = $3",
+            )
+            .bind(item_id)
+            .bind(result)
+            .bind(job.id)
+        }
+        Err(e) if is_transient(&e) && job.attempts < max_attempts => {
+            let delay = RETRY_BASE_DELAY_SECS << (job.attempts - 1).min(10);
+            warn!("Scrape job {} failed ({}); retrying in {}s", job.id, e, delay);
+            sqlx::query(
+                "UPDATE scrape_jobs SET status = 'pending', error = $1, run_after = datetime('now', $2),
+                     updated_at = datetime('now') WHERE id = $3",
+            )
+            .bind(e.to_string())
+            .bind(format!("+{} seconds", delay))
+            .bind(job.id)
+        }
+        Err(e) => {
+            warn!("Scrape job {} failed: {}", job.id, e);
+            sqlx::query(
+                "UPDATE scrape_jobs SET status = 'failed', error = $1, headers = NULL,
+                     updated_at = datetime('now') WHERE id = $2",
+            )
+            .bind(e.to_string())
+            .bind(job.id)
+        }
+    };
+    if let Err(e) = update.execute(&state.db).await {
+        error!("Failed to record the outcome of scrape job {}: {}", job.id, e);
+    }
+}
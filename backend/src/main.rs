@@ -1,8 +1,9 @@
 use axum::{
-    extract::{Path, State},
-    http::{Method, StatusCode},
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware,
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{delete, get, post},
     Json, Router,
 };
 use firecrawl::{
@@ -11,18 +12,71 @@ use firecrawl::{
     FirecrawlError,
 };
 use serde::{Deserialize, Serialize};
-use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use sqlx::QueryBuilder;
+use metrics_exporter_prometheus::PrometheusHandle;
+use std::collections::{HashMap, HashSet};
 use std::env;
-use std::net::SocketAddr;
-use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
-use tracing::{error, info, instrument}; // Import instrument
+use std::fmt::Display;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::{Notify, Semaphore};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tracing::{error, info, instrument, warn}; // Import instrument
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+mod auth;
+mod batch;
+mod crawl;
+mod db;
+mod duplicates;
+mod export;
+mod forward;
+mod health;
+mod jobs;
+mod markdown;
+mod output;
+mod rate_limit;
+mod readability;
+mod refresh;
+mod retry;
+mod revisions;
+mod search;
+mod stats;
+mod tags;
+mod telemetry;
+mod trash;
+
+use crawl::CrawlLimits;
+use db::{Db, DbPool};
+use forward::{HeaderPolicy, Secret};
+use retry::RetryPolicy;
+
+// The Firecrawl SDK does not expose a viewport option, so mobile renderings are
+// requested the way responsive servers detect them: with a mobile User-Agent.
+const MOBILE_USER_AGENT: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Mobile/15E148 Safari/604.1";
+
+// Narrower wrapping would split most sentences after a word or two
+const MIN_WRAP_WIDTH: usize = 20;
+
 // Shared application state
 struct AppState {
-    db: SqlitePool,
-    firecrawl_app: FirecrawlApp,
+    db: DbPool,
+    firecrawl_app: Option<FirecrawlApp>, // None when FIRECRAWL_API_KEY is unset or still the placeholder
+    firecrawl_retry: RetryPolicy, // Timeout and retries for each Firecrawl scrape
+    crawl_limits: CrawlLimits,
+    metrics_handle: PrometheusHandle,
+    metrics_token: Option<String>, // Bearer token required by /metrics when set
+    output_dir: Option<PathBuf>, // When set, scraped Markdown is also written here as files
+    upgrade_http: bool, // Try https:// first for http:// URLs, falling back to http on failure
+    active_crawls: Mutex<HashSet<i64>>, // Crawls currently being run, so one can't be resumed twice at once
+    stream_permits: Arc<Semaphore>, // One permit per open SSE subscriber
+    max_stream_subscribers: usize,
+    header_policy: HeaderPolicy, // Client headers passed on to Firecrawl when scraping
+    job_notify: Notify, // Wakes an idle job worker when a scrape job is queued
+    scrape_rate_limiter: Arc<rate_limit::RateLimiter>, // Per-IP budget for scrape requests
 }
 
 // Data structures
@@ -32,36 +86,238 @@ struct ScrapedItem {
     url: String,
     content: String, // Will now store Markdown content
     created_at: String, // Using TEXT for simplicity, consider DATETIME
+    images_stripped: bool, // Image references were removed before storing
+    content_hash: Option<String>, // SHA-256 of `content`; NULL for rows stored before hashing
+    duplicate_of: Option<i64>, // Earlier item with identical content under a different URL
+    accept_language: Option<String>, // Accept-Language the item was scraped with, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deleted_at: Option<String>, // Set while the item is in the trash
+    #[serde(skip_serializing_if = "Option::is_none")]
+    readability: Option<bool>, // Set once readability was requested: whether an article was extracted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    article: Option<String>, // The readability article, kept alongside the full `content`
+    word_count: Option<i64>, // Words in `content`; NULL (unknown) for items stored before counting
+    source: Option<String>, // Scraper that produced the item, e.g. "firecrawl"; NULL for older items
+    options: Option<sqlx::types::Json<serde_json::Value>>, // Options sent to `source`, without headers
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    metadata: PageMetadata,
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    formats: ExtraFormats,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[sqlx(skip)]
+    tags: Option<Vec<String>>, // Loaded separately, by the endpoints that return tags
 }
 
-#[derive(Deserialize, Debug)]
+// Page metadata reported by Firecrawl; None when the page doesn't provide it
+#[derive(Serialize, Deserialize, sqlx::FromRow, Default, Clone)]
+struct PageMetadata {
+    title: Option<String>,
+    description: Option<String>,
+}
+
+// Length of the returned content; None when unknown (items stored before counting)
+#[derive(Serialize)]
+struct ReadingStats {
+    word_count: Option<u32>,
+    reading_time_minutes: Option<u32>, // At WORDS_PER_MINUTE, rounded up
+}
+
+// Reading speed behind `reading_time_minutes`
+const WORDS_PER_MINUTE: u32 = 200;
+
+impl ReadingStats {
+    fn new(word_count: Option<u32>) -> Self {
+        Self {
+            word_count,
+            reading_time_minutes: word_count.map(|words| words.div_ceil(WORDS_PER_MINUTE)),
+        }
+    }
+
+    fn of(content: &str) -> Self {
+        Self::new(Some(markdown::word_count(content)))
+    }
+}
+
+// Representations of a page besides Markdown, stored when requested with `formats`
+#[derive(Serialize, Deserialize, sqlx::FromRow, Default, Clone)]
+struct ExtraFormats {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    html: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    raw_html: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    links: Option<sqlx::types::Json<Vec<String>>>, // Stored as a JSON array
+}
+
+impl ExtraFormats {
+    // Just the formats in `requested`
+    fn only(self, requested: &[ScrapeFormats]) -> Self {
+        let wanted = |format: ScrapeFormats| requested.iter().any(|f| same_format(f, &format));
+        Self {
+            html: self.html.filter(|_| wanted(ScrapeFormats::HTML)),
+            raw_html: self.raw_html.filter(|_| wanted(ScrapeFormats::RawHTML)),
+            links: self.links.filter(|_| wanted(ScrapeFormats::Links)),
+        }
+    }
+
+    // Whether every format in `requested` is present
+    fn has_all(&self, requested: &[ScrapeFormats]) -> bool {
+        requested.iter().all(|format| match format {
+            ScrapeFormats::HTML => self.html.is_some(),
+            ScrapeFormats::RawHTML => self.raw_html.is_some(),
+            ScrapeFormats::Links => self.links.is_some(),
+            _ => true,
+        })
+    }
+}
+
+// Firecrawl's ScrapeFormats has no PartialEq, so formats compare by variant
+fn same_format(a: &ScrapeFormats, b: &ScrapeFormats) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
+// `formats` names accepted by POST /scrape; Markdown is always returned
+const FORMAT_NAMES: &[(&str, ScrapeFormats)] = &[
+    ("markdown", ScrapeFormats::Markdown),
+    ("html", ScrapeFormats::HTML),
+    ("rawHtml", ScrapeFormats::RawHTML),
+    ("links", ScrapeFormats::Links),
+];
+
+// Recorded as the `source` of items scraped through Firecrawl
+const FIRECRAWL_SOURCE: &str = "firecrawl";
+
+// Column list matching `ScrapedItem`, shared by the queries that load items
+const ITEM_COLUMNS: &str =
+    "id, url, content, created_at, images_stripped, content_hash, duplicate_of, accept_language, deleted_at, readability, article, word_count, source, options, title, description, html, raw_html, links";
+
+#[derive(Serialize, Deserialize, Debug, Default)]
 struct ScrapeRequest {
     url: String,
+    #[serde(default)]
+    mobile: bool, // Also scrape (and store) the mobile-viewport variant
+    #[serde(default)]
+    exclude_images: bool, // Strip image Markdown/HTML before storing
+    #[serde(default)]
+    accept_language: Option<String>, // Forwarded to the scraped site, e.g. "de-DE,de;q=0.9"
+    #[serde(default)]
+    wrap_width: Option<usize>, // Hard-wrap the returned Markdown at this many columns; stored content is unwrapped
+    #[serde(default)]
+    include_plain_text: bool, // Also return (and store) a plain-text rendering of the Markdown
+    #[serde(default)]
+    no_store: bool, // Scrape fresh and never touch the DB; the response is marked `Cache-Control: no-store`
+    #[serde(default)]
+    force: bool, // Re-scrape even when the URL is cached, refreshing the stored item
+    #[serde(default)]
+    formats: Option<Vec<String>>, // Representations to return and store, from FORMAT_NAMES
+    #[serde(default)]
+    readability: bool, // Extract just the main article from the page's HTML, reader-view style
+    #[serde(default, rename = "async")]
+    run_async: bool, // Queue the scrape as a job and return its id instead of waiting
+    #[serde(default, skip_serializing)]
+    username: Option<Secret>, // HTTP basic auth for the scraped site; never stored or logged
+    #[serde(default, skip_serializing)]
+    password: Option<Secret>,
+}
+
+impl ScrapeRequest {
+    // The requested `formats` besides Markdown, or a 400 naming every unknown
+    // one along with the valid ones
+    fn extra_formats(&self) -> Result<Vec<ScrapeFormats>, AppError> {
+        let mut formats = Vec::new();
+        let mut unknown = Vec::new();
+        for name in self.formats.iter().flatten() {
+            match FORMAT_NAMES.iter().find(|(known, _)| known == name) {
+                Some((_, ScrapeFormats::Markdown)) => {}
+                Some((_, format)) if !formats.iter().any(|f| same_format(f, format)) => formats.push(*format),
+                Some(_) => {}
+                None => unknown.push(format!("{:?}", name)),
+            }
+        }
+        if !unknown.is_empty() {
+            let valid: Vec<&str> = FORMAT_NAMES.iter().map(|(known, _)| *known).collect();
+            return Err(AppError::BadRequest(format!(
+                "Unsupported format{} {}; supported formats are {}",
+                if unknown.len() == 1 { "" } else { "s" },
+                unknown.join(", "),
+                valid.join(", ")
+            )));
+        }
+        Ok(formats)
+    }
 }
 
 #[derive(Serialize)]
 struct ScrapeResponse {
-    id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<i64>, // None for `no_store` scrapes, which are never saved
     url: String,
     content: String, // Send back Markdown content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mobile_content: Option<String>, // Markdown of the mobile variant, when requested
+    images_stripped: bool,
+    duplicate_of: Option<i64>, // Set when identical content is already stored under another URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    plain_text: Option<String>, // Formatting-free text of `content`, when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    readability: Option<bool>, // When requested: true if an article was extracted, false if it fell back
+    #[serde(flatten)]
+    reading: ReadingStats, // Of `content` before wrapping
+    #[serde(flatten)]
+    metadata: PageMetadata,
+    #[serde(flatten)]
+    formats: ExtraFormats, // The extra `formats` that were requested
+    cached: bool, // Served from the database rather than scraped just now
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scraped_at: Option<String>, // When the stored content was scraped; None for `no_store` scrapes
+}
+
+#[derive(Deserialize, Debug)]
+struct HistoryQuery {
+    tag: Option<String>, // Only items with this tag
+    #[serde(default, alias = "include_archived")]
+    include_trashed: bool, // Also list items in the trash; they have `deleted_at` set
+}
+
+#[derive(Deserialize, Debug)]
+struct PurgeCacheQuery {
+    url: String,
+}
+
+#[derive(Serialize)]
+struct PurgeCacheResponse {
+    url: String,
+    removed: bool, // Whether a cached row existed and was deleted
 }
 
 #[derive(Serialize)]
 struct ErrorResponse {
+    code: &'static str, // Machine-readable kind of error, e.g. "NOT_FOUND"
     message: String,
 }
 
 // Custom Error Type
+#[derive(Debug)]
 enum AppError {
     Sqlx(sqlx::Error),
     Firecrawl(FirecrawlError),
     Internal(String),
+    BadRequest(String),
     NotFound(String),
+    Conflict(String),
+    ServiceUnavailable(String),
+    Unauthorized(String), // The scraped site rejected the credentials it was sent
+    Timeout(String), // Firecrawl didn't answer in time, retries included
 }
 
 // Implement IntoResponse for AppError to convert errors into HTTP responses
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        metrics::counter!(telemetry::ERRORS_TOTAL, "type" => self.kind()).increment(1);
+
+        let code = self.code();
         let (status, error_message) = match self {
             AppError::Sqlx(e) => {
                 error!("Database error: {}", e);
@@ -81,10 +337,22 @@ impl IntoResponse for AppError {
                 error!("Internal server error: {}", msg);
                 (StatusCode::INTERNAL_SERVER_ERROR, msg)
             }
+            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            AppError::ServiceUnavailable(msg) => {
+                warn!("Service unavailable: {}", msg);
+                (StatusCode::SERVICE_UNAVAILABLE, msg)
+            }
+            AppError::Timeout(msg) => {
+                warn!("Gateway timeout: {}", msg);
+                (StatusCode::GATEWAY_TIMEOUT, msg)
+            }
         };
 
         let body = Json(ErrorResponse {
+            code,
             message: error_message,
         });
 
@@ -92,6 +360,64 @@ impl IntoResponse for AppError {
     }
 }
 
+// Human-readable description, for errors that are recorded rather than returned
+impl Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Sqlx(e) => write!(f, "Database operation failed: {}", e),
+            AppError::Firecrawl(e) => write!(f, "Scraping service failed: {}", e),
+            AppError::Internal(msg)
+            | AppError::BadRequest(msg)
+            | AppError::NotFound(msg)
+            | AppError::Conflict(msg)
+            | AppError::ServiceUnavailable(msg)
+            | AppError::Unauthorized(msg)
+            | AppError::Timeout(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl AppError {
+    // The `code` of error responses, for clients to branch on
+    fn code(&self) -> &'static str {
+        match self {
+            AppError::Sqlx(_) => "DB_ERROR",
+            AppError::Firecrawl(_) => "SCRAPE_FAILED",
+            AppError::Internal(_) => "INTERNAL",
+            AppError::BadRequest(_) => "BAD_REQUEST",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Conflict(_) => "CONFLICT",
+            AppError::ServiceUnavailable(_) => "SERVICE_UNAVAILABLE",
+            AppError::Unauthorized(_) => "SITE_UNAUTHORIZED",
+            AppError::Timeout(_) => "TIMEOUT",
+        }
+    }
+
+    // Short label used for the `type` of the errors_total metric
+    fn kind(&self) -> &'static str {
+        match self {
+            AppError::Sqlx(_) => "database",
+            AppError::Firecrawl(_) => "firecrawl",
+            AppError::Internal(_) => "internal",
+            AppError::BadRequest(_) => "bad_request",
+            AppError::NotFound(_) => "not_found",
+            AppError::Conflict(_) => "conflict",
+            AppError::ServiceUnavailable(_) => "unavailable",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::Timeout(_) => "timeout",
+        }
+    }
+}
+
+impl AppState {
+    // The Firecrawl client, or a 503 when the server was started without an API key
+    fn firecrawl(&self) -> Result<&FirecrawlApp, AppError> {
+        self.firecrawl_app
+            .as_ref()
+            .ok_or_else(|| AppError::ServiceUnavailable("Firecrawl not configured".to_string()))
+    }
+}
+
 impl From<sqlx::Error> for AppError {
     fn from(err: sqlx::Error) -> Self {
         match err {
@@ -107,6 +433,42 @@ impl From<FirecrawlError> for AppError {
     }
 }
 
+// Origins browsers may call the API from, from the comma-separated
+// CORS_ALLOWED_ORIGINS; any origin when it's unset, which suits local development
+fn allowed_origins() -> AllowOrigin {
+    let origins: Vec<HeaderValue> = env::var("CORS_ALLOWED_ORIGINS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|origin| origin.trim().trim_end_matches('/'))
+        .filter(|origin| !origin.is_empty())
+        .map(|origin| {
+            HeaderValue::from_str(origin)
+                .unwrap_or_else(|e| panic!("CORS_ALLOWED_ORIGINS has an invalid origin {:?}: {}", origin, e))
+        })
+        .collect();
+    if origins.is_empty() {
+        warn!("CORS_ALLOWED_ORIGINS not set; allowing requests from any origin");
+        return AllowOrigin::any();
+    }
+    info!("CORS allowed origins: {:?}", origins);
+    AllowOrigin::list(origins)
+}
+
+// Reads an optional env var, falling back to `default` when unset.
+// Panics with a clear message if the variable is set but fails to parse.
+fn env_parse_or<T>(key: &str, default: T) -> T
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match env::var(key) {
+        Ok(value) => value
+            .parse()
+            .unwrap_or_else(|e| panic!("{} has an invalid value {:?}: {}", key, value, e)),
+        Err(_) => default,
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load environment variables from .env file
@@ -120,152 +482,847 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    let metrics_handle = telemetry::install_recorder();
+    let api_key: auth::ApiKey = env::var("API_KEY").ok().filter(|key| !key.is_empty()).map(Arc::from);
+    if api_key.is_none() {
+        warn!("API_KEY not set; anyone who can reach the server can start scrapes");
+    }
+    let metrics_token = env::var("METRICS_TOKEN").ok().filter(|token| !token.is_empty());
+    if metrics_token.is_none() {
+        info!("METRICS_TOKEN not set; /metrics is unauthenticated");
+    }
+
     info!("Initializing database connection...");
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-
-    // Create SQLite connection pool
-    let pool = SqlitePoolOptions::new()
-        .max_connections(5)
-        .connect(&database_url)
-        .await
-        .expect("Failed to create database pool");
-
-    info!("Running database migrations (creating table if needed)...");
-    // Create table if it doesn't exist
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS scraped_items (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            url TEXT NOT NULL UNIQUE,
-            content TEXT NOT NULL,
-            created_at TEXT DEFAULT CURRENT_TIMESTAMP
-        )
-        "#,
-    )
-    .execute(&pool)
-    .await
-    .expect("Failed to run database migrations");
+    let pool = match db::connect(&database_url).await {
+        Ok(pool) => pool,
+        Err(message) => {
+            error!("{}", message);
+            return Err(message.into());
+        }
+    };
 
     info!("Database initialized successfully.");
 
     info!("Initializing Firecrawl client...");
-    let firecrawl_api_key = env::var("FIRECRAWL_API_KEY").expect("FIRECRAWL_API_KEY must be set");
-    if firecrawl_api_key == "YOUR_FIRECRAWL_API_KEY" {
-        error!("Placeholder FIRECRAWL_API_KEY found. Please set it in .env");
-        panic!("FIRECRAWL_API_KEY not configured");
+    // A missing or placeholder key only disables scraping; stored history stays available
+    let firecrawl_app = match env::var("FIRECRAWL_API_KEY") {
+        Ok(key) if !key.is_empty() && key != "YOUR_FIRECRAWL_API_KEY" => {
+            let app = FirecrawlApp::new(key)?;
+            info!("Firecrawl client initialized.");
+            Some(app)
+        }
+        _ => {
+            warn!("FIRECRAWL_API_KEY is not set (or is the placeholder). Firecrawl scraping is disabled; set it in .env to enable it");
+            None
+        }
+    };
+
+    let firecrawl_retry = RetryPolicy::from_env();
+    info!(
+        "Firecrawl timeout: {:?} per attempt, up to {} attempts",
+        firecrawl_retry.timeout, firecrawl_retry.attempts
+    );
+
+    let output_dir = env::var("OUTPUT_DIR").ok().filter(|dir| !dir.is_empty()).map(PathBuf::from);
+    if let Some(dir) = &output_dir {
+        info!("Writing scraped Markdown files to {}", dir.display());
     }
-    let firecrawl_app = FirecrawlApp::new(firecrawl_api_key)?;
-    info!("Firecrawl client initialized.");
+
+    let upgrade_http = env_parse_or("UPGRADE_HTTP", true);
+    info!("Upgrade http:// URLs to https:// before scraping: {}", upgrade_http);
+
+    // Soft-deleted items stay restorable for this long before being purged
+    let trash_retention_days = env_parse_or("TRASH_RETENTION_DAYS", 7u32);
+    info!("Purging deleted items after {} days", trash_retention_days);
+    trash::spawn_purge_task(pool.clone(), trash_retention_days);
+
+    // Caps concurrently open progress streams so subscribers can't exhaust the server
+    let max_stream_subscribers = env_parse_or("MAX_STREAM_SUBSCRIBERS", 32usize);
+    info!("Max concurrent stream subscribers: {}", max_stream_subscribers);
+
+    let header_policy = HeaderPolicy::from_env();
+    let forwarded_headers: Vec<&str> = header_policy.allowed().collect();
+    if forwarded_headers.is_empty() {
+        info!("Not forwarding any client headers (FORWARD_HEADERS is empty)");
+    } else {
+        info!("Forwarding client headers: {}", forwarded_headers.join(", "));
+    }
+
+    let scrape_rate_limit = env_parse_or("SCRAPE_RATE_LIMIT", 10u32);
+    if scrape_rate_limit == 0 {
+        info!("Scrape rate limiting disabled (SCRAPE_RATE_LIMIT=0)");
+    } else {
+        info!("Scrape rate limit: {} requests per minute per client IP", scrape_rate_limit);
+    }
+
+    let crawl_limits = CrawlLimits::from_env();
+    info!(
+        "Crawl caps: max_depth {}, max_pages {}",
+        crawl_limits.max_depth, crawl_limits.max_pages
+    );
 
     // Create shared state
     let shared_state = Arc::new(AppState {
-        db: pool,
+        db: pool.clone(),
         firecrawl_app,
+        firecrawl_retry,
+        crawl_limits,
+        metrics_handle,
+        metrics_token,
+        output_dir,
+        upgrade_http,
+        active_crawls: Mutex::new(HashSet::new()),
+        stream_permits: Arc::new(Semaphore::new(max_stream_subscribers)),
+        max_stream_subscribers,
+        header_policy,
+        job_notify: Notify::new(),
+        scrape_rate_limiter: Arc::new(rate_limit::RateLimiter::new(scrape_rate_limit)),
     });
 
-    // Configure CORS
+    // Background workers for `async` scrapes
+    let job_settings = jobs::JobSettings::from_env();
+    info!(
+        "Starting {} scrape job worker(s): poll every {:?}, up to {} attempts per job",
+        job_settings.workers, job_settings.poll_interval, job_settings.max_attempts
+    );
+    let job_workers = jobs::start_workers(shared_state.clone(), job_settings).await;
+
     let cors = CorsLayer::new()
-        // Allow requests from any origin - adjust in production!
-        .allow_origin(Any)
-        .allow_methods([Method::GET, Method::POST])
+        .allow_origin(allowed_origins())
+        .allow_methods([Method::GET, Method::POST, Method::DELETE])
         .allow_headers(Any);
 
     // Build application routes
-    let app = Router::new()
+    // Routes that spend Firecrawl quota or change stored data need the API key when one is set
+    let protected_routes = Router::new()
         .route("/scrape", post(scrape_handler))
+        .route("/scrape/batch", post(batch::batch_scrape_handler))
+        .route_layer(middleware::from_fn_with_state(
+            shared_state.scrape_rate_limiter.clone(),
+            rate_limit::limit_by_ip,
+        ))
+        .route("/crawl", post(crawl::crawl_handler))
+        .route("/crawl/:id/resume", post(crawl::resume_crawl_handler))
+        .route("/refresh", post(refresh::refresh_handler))
+        .route("/cache", delete(purge_cache_handler))
+        .route("/history/tag", post(tags::bulk_tag_handler))
+        .route("/history/:id", delete(trash::delete_item_handler))
+        .route("/history/:id/restore", post(trash::restore_item_handler))
+        .route("/history/:id/tags", post(tags::add_tag_handler))
+        .route("/history/:id/tags/:tag", delete(tags::remove_tag_handler))
+        .route_layer(middleware::from_fn_with_state(api_key, auth::require_api_key));
+    let app = Router::new()
+        .merge(protected_routes)
+        .route("/crawl/:id/events", get(crawl::crawl_events_handler))
+        .route("/health", get(health::health_handler))
+        .route("/metrics", get(telemetry::metrics_handler))
+        .route("/export", get(export::export_handler))
         .route("/history", get(get_history_handler))
         .route("/history/:id", get(get_item_handler))
+        .route("/history/:id/revisions", get(revisions::list_revisions_handler))
+        .route("/history/:id/diff", get(revisions::diff_handler))
+        .route("/trash", get(trash::list_trash_handler))
+        .route("/duplicates", get(duplicates::duplicates_handler))
+        .route("/search", get(search::search_handler))
+        .route("/stats", get(stats::stats_handler))
+        .route("/jobs/:id", get(jobs::get_job_handler))
         .with_state(shared_state)
         .layer(cors) // Apply CORS middleware
         .layer(tower_http::trace::TraceLayer::new_for_http()); // Apply tracing
 
-    // Define the server address
-    let addr = SocketAddr::from(([127, 0, 0, 1], 8000)); // Changed port to 8000
-    info!("Server listening on {}", addr);
+    // Define the server address; BIND_ADDR=0.0.0.0 exposes it outside localhost (e.g. in Docker)
+    let bind_addr = env_parse_or("BIND_ADDR", IpAddr::from([127, 0, 0, 1]));
+    let port = env_parse_or("PORT", 8000u16);
+    let addr = SocketAddr::new(bind_addr, port);
+    info!("Binding to {}", addr);
 
     // Run the server
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .inspect_err(|e| error!("Failed to bind {}: {}", addr, e))?;
+    info!("Server listening on {}", addr);
+    // Connection info gives the rate limiter each client's IP
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
 
+    job_workers.shutdown().await;
+    // Waits for connections still in use (e.g. by background crawls) to be returned
+    pool.close().await;
+    info!("Database pool closed");
     Ok(())
 }
 
+// Resolves on Ctrl+C or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("Failed to listen for Ctrl+C");
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    info!("Shutting down, draining connections");
+}
+
 // --- API Handlers ---
 
-#[instrument(skip(state))] // Instrument the handler, skipping the state
+#[instrument(skip(state, headers))] // Instrument the handler, skipping the state
 async fn scrape_handler(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(payload): Json<ScrapeRequest>,
-) -> Result<Json<ScrapeResponse>, AppError> {
+) -> Result<Response, AppError> {
     info!("Received scrape request for URL: {}", payload.url);
+    metrics::counter!(telemetry::SCRAPES_TOTAL).increment(1);
 
-    // 1. Check if URL already exists in DB
-    let existing_item: Option<ScrapedItem> = sqlx::query_as("SELECT * FROM scraped_items WHERE url = ?1")
-        .bind(&payload.url)
-        .fetch_optional(&state.db)
-        .await?;
+    let mut forwarded = state.header_policy.forwarded(&headers);
+    let forwarded_language = take_forwarded_language(&mut forwarded);
+    let accept_language = payload
+        .accept_language
+        .as_deref()
+        .map(str::trim)
+        .filter(|lang| !lang.is_empty())
+        .or(forwarded_language.as_deref());
+    if let Some(lang) = accept_language {
+        if !is_valid_accept_language(lang) {
+            return Err(AppError::BadRequest(format!("Invalid accept_language {:?}", lang)));
+        }
+    }
+
+    // Explicit credentials replace any forwarded Authorization header
+    match (&payload.username, &payload.password) {
+        (Some(username), password) => {
+            if username.expose().contains(':') {
+                return Err(AppError::BadRequest("username can't contain ':'".to_string()));
+            }
+            let password = password.as_ref().map_or("", Secret::expose);
+            forwarded.insert("authorization".to_string(), forward::basic_auth(username.expose(), password));
+        }
+        (None, Some(_)) => return Err(AppError::BadRequest("password requires a username".to_string())),
+        (None, None) => {}
+    }
+
+    payload.extra_formats()?;
+
+    if let Some(width) = payload.wrap_width.filter(|&width| width < MIN_WRAP_WIDTH) {
+        return Err(AppError::BadRequest(format!(
+            "wrap_width must be at least {} (got {})",
+            MIN_WRAP_WIDTH, width
+        )));
+    }
+
+    if payload.run_async {
+        if payload.no_store {
+            return Err(AppError::BadRequest("no_store can't be combined with async".to_string()));
+        }
+        if payload.username.is_some() {
+            // Jobs are stored; credentials must not be
+            return Err(AppError::BadRequest("username/password can't be combined with async".to_string()));
+        }
+        // The worker gets the language as resolved here, forwarded or not
+        let payload = ScrapeRequest {
+            accept_language: accept_language.map(str::to_string),
+            ..payload
+        };
+        let job_id = jobs::enqueue(&state, &payload, &forwarded).await?;
+        let body = Json(serde_json::json!({ "job_id": job_id, "status": "pending" }));
+        return Ok((StatusCode::ACCEPTED, body).into_response());
+    }
+
+    let started = Instant::now();
+    let response = run_scrape(&state, &payload, accept_language, &forwarded).await?;
+    // Structured, so log collectors can aggregate it
+    info!(
+        url = %payload.url,
+        duration_ms = started.elapsed().as_millis() as u64,
+        content_bytes = response.content.len(),
+        cache_hit = response.cached,
+        "Scrape completed"
+    );
+    if payload.no_store || is_authenticated(&forwarded) {
+        // Nothing was stored; make sure no intermediary keeps a copy either
+        return Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)).into_response());
+    }
+    Ok(Json(response).into_response())
+}
+
+// A forwarded Accept-Language stands in for an explicit `accept_language`, so
+// it's taken out of the forwarded headers and the cache treats it the same
+// way; malformed ones are dropped
+fn take_forwarded_language(forwarded: &mut HashMap<String, String>) -> Option<String> {
+    forwarded
+        .remove("accept-language")
+        .map(|lang| lang.trim().to_string())
+        .filter(|lang| !lang.is_empty() && is_valid_accept_language(lang))
+}
+
+// Scrapes per an already validated request: from the cache when possible,
+// otherwise with Firecrawl, storing the result unless `no_store` is set.
+// Shared by `POST /scrape` and the job worker.
+async fn run_scrape(
+    state: &AppState,
+    payload: &ScrapeRequest,
+    accept_language: Option<&str>,
+    forwarded: &HashMap<String, String>,
+) -> Result<ScrapeResponse, AppError> {
+    let upgraded_url = https_upgrade(&payload.url).filter(|_| state.upgrade_http);
+    let extra_formats = payload.extra_formats()?;
+    // Content behind auth depends on who asked, so it's never cached or
+    // served from the cache
+    if payload.no_store || is_authenticated(forwarded) {
+        return scrape_without_storing(state, payload, upgraded_url, accept_language, forwarded, &extra_formats).await;
+    }
+
+    // 1. Check if URL already exists in DB, under the upgraded https:// URL too
+    let existing_item: Option<ScrapedItem> = sqlx::query_as(&format!(
+        "SELECT {} FROM scraped_items WHERE url IN ($1, $2) AND deleted_at IS NULL ORDER BY url = $2 DESC LIMIT 1",
+        ITEM_COLUMNS
+    ))
+    .bind(&payload.url)
+    .bind(upgraded_url.as_deref().unwrap_or(&payload.url))
+    .fetch_optional(&state.db)
+    .await?;
+
+    // A cached item only counts when it was scraped in the requested language.
+    // One that is re-scraped is updated in place, whichever of the two URLs it
+    // is stored under; its variants go when they no longer match.
+    let (existing_item, replaced_item, variants_outdated) = match existing_item {
+        Some(item) if payload.force => {
+            info!("Forced re-scrape of {}; skipping the cache", item.url);
+            (None, Some((item.id, item.url)), true)
+        }
+        Some(item) if accept_language.is_some_and(|lang| item.accept_language.as_deref() != Some(lang)) => {
+            info!("URL {} is cached in another language; re-scraping", item.url);
+            (None, Some((item.id, item.url)), true)
+        }
+        Some(item) if payload.readability && item.article.is_none() && item.readability != Some(false) => {
+            info!("URL {} is cached without a readability pass; re-scraping", item.url);
+            (None, Some((item.id, item.url)), false)
+        }
+        Some(item) if !item.formats.has_all(&extra_formats) => {
+            info!("URL {} is cached without some of the requested formats; re-scraping", item.url);
+            (None, Some((item.id, item.url)), false)
+        }
+        item => (item, None, false),
+    };
 
     if let Some(item) = existing_item {
         info!("URL {} found in database (ID: {}). Returning cached Markdown.", item.url, item.id);
-        return Ok(Json(ScrapeResponse {
-            id: item.id,
+        metrics::counter!(telemetry::SCRAPE_CACHE_HITS_TOTAL).increment(1);
+        let mobile_content = if payload.mobile {
+            Some(get_or_scrape_mobile_variant(state, item.id, &item.url, item.accept_language.as_deref(), forwarded).await?)
+        } else {
+            None
+        };
+        // Both versions are stored; readability requests get the article when there is one
+        let (content, readability, reading) = match item.article {
+            Some(article) if payload.readability => {
+                let reading = ReadingStats::of(&article);
+                (article, Some(true), reading)
+            }
+            _ => (item.content, payload.readability.then_some(false), ReadingStats::new(item.word_count.and_then(|words| u32::try_from(words).ok()))),
+        };
+        let plain_text = match (payload.include_plain_text, readability) {
+            (false, _) => None,
+            (true, Some(true)) => Some(markdown::to_plain_text(&content)),
+            (true, _) => Some(get_or_store_plain_text(&state.db, item.id, &content).await?),
+        };
+        // Items cached with images are stripped on the way out when asked
+        let (content, images_stripped) = if payload.exclude_images && !item.images_stripped {
+            (markdown::strip_images(&content), true)
+        } else {
+            (content, item.images_stripped)
+        };
+        return Ok(ScrapeResponse {
+            id: Some(item.id),
             url: item.url,
-            content: item.content, // Return stored Markdown
-        }));
+            content: wrap_if(payload.wrap_width, content), // Return stored Markdown
+            mobile_content: mobile_content.map(|c| wrap_if(payload.wrap_width, strip_images_if(payload.exclude_images, c))),
+            images_stripped,
+            duplicate_of: item.duplicate_of,
+            plain_text,
+            readability,
+            reading,
+            metadata: item.metadata,
+            formats: item.formats.only(&extra_formats),
+            cached: true,
+            scraped_at: Some(item.created_at),
+        });
     }
 
     // 2. If not exists, scrape the URL using Firecrawl
     info!("URL {} not found in DB. Scraping with Firecrawl...", payload.url);
 
+    let (url, page, article) =
+        scrape_content(state, payload, upgraded_url, accept_language, forwarded, &extra_formats).await?;
+    let ScrapedPage { markdown: markdown_content, formats, metadata, options } = page;
+    let markdown_content = strip_images_if(payload.exclude_images, markdown_content);
+    let article = article.map(|article| strip_images_if(payload.exclude_images, article));
+    let readability = payload.readability.then_some(article.is_some());
+    let content_hash = markdown::content_hash(&markdown_content);
+    let word_count = markdown::word_count(&markdown_content);
+    let stored_plain_text = payload.include_plain_text.then(|| markdown::to_plain_text(&markdown_content));
+    let duplicate_of = find_duplicate(&state.db, &content_hash, &url).await?;
+    if let Some(original_id) = duplicate_of {
+        info!("Content of {} is identical to item {}", url, original_id);
+    }
+
+    info!(
+        "Successfully scraped {} using Firecrawl ({} bytes of Markdown)",
+        url,
+        markdown_content.len()
+    );
+
+    // 3. Insert Markdown content into database, replacing the item being re-scraped
+    //    (it keeps its id). The item moves to the URL that was scraped first, so
+    //    the upsert below finds it when that is the other scheme.
+    let mut tx = state.db.begin().await?;
+    if let Some((replaced_id, replaced_url)) = &replaced_item {
+        if *replaced_url != url {
+            // Unless a trashed item holds that URL; it is then revived instead
+            sqlx::query(
+                "UPDATE scraped_items SET url = $1
+                 WHERE id = $2 AND NOT EXISTS (SELECT 1 FROM scraped_items WHERE url = $1)",
+            )
+            .bind(&url)
+            .bind(replaced_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+    let (new_id, scraped_at): (i64, String) = sqlx::query_as(
+        "INSERT INTO scraped_items
+             (url, content, images_stripped, content_hash, duplicate_of, accept_language, plain_text, html, raw_html, links, readability, article,
+              word_count, title, description, source, options)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+         ON CONFLICT(url) DO UPDATE SET content = excluded.content, images_stripped = excluded.images_stripped,
+             content_hash = excluded.content_hash, duplicate_of = excluded.duplicate_of,
+             accept_language = excluded.accept_language, plain_text = excluded.plain_text,
+             html = excluded.html, raw_html = excluded.raw_html, links = excluded.links,
+             readability = excluded.readability, article = excluded.article,
+             word_count = excluded.word_count, title = excluded.title, description = excluded.description,
+             source = excluded.source, options = excluded.options, deleted_at = NULL,
+             created_at = datetime('now')
+         RETURNING id, created_at",
+    )
+    .bind(&url)
+    .bind(&markdown_content) // Store Markdown content
+    .bind(payload.exclude_images)
+    .bind(&content_hash)
+    .bind(duplicate_of)
+    .bind(accept_language)
+    .bind(&stored_plain_text)
+    .bind(&formats.html)
+    .bind(&formats.raw_html)
+    .bind(&formats.links)
+    .bind(readability)
+    .bind(&article)
+    .bind(i64::from(word_count))
+    .bind(&metadata.title)
+    .bind(&metadata.description)
+    .bind(FIRECRAWL_SOURCE)
+    .bind(&options)
+    .fetch_one(&mut *tx)
+    .await?;
+    if variants_outdated {
+        // Scraped in the old language, or from the content being refreshed
+        sqlx::query("DELETE FROM scraped_variants WHERE item_id = $1")
+            .bind(new_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    info!("Successfully inserted Markdown for URL {} with ID {}", url, new_id);
+
+    if let Some(dir) = &state.output_dir {
+        output::write_markdown_file(dir, &url, new_id, &markdown_content).await;
+    }
+
+    let mobile_content = if payload.mobile {
+        Some(get_or_scrape_mobile_variant(state, new_id, &url, accept_language, forwarded).await?)
+    } else {
+        None
+    };
+
+    // Return the newly scraped Markdown content, or the article when one was found
+    let (content, plain_text, reading) = match article {
+        Some(article) => {
+            let plain_text = payload.include_plain_text.then(|| markdown::to_plain_text(&article));
+            let reading = ReadingStats::of(&article);
+            (article, plain_text, reading)
+        }
+        None => (markdown_content, stored_plain_text, ReadingStats::new(Some(word_count))),
+    };
+    Ok(ScrapeResponse {
+        id: Some(new_id),
+        url,
+        content: wrap_if(payload.wrap_width, content),
+        mobile_content: mobile_content.map(|c| wrap_if(payload.wrap_width, strip_images_if(payload.exclude_images, c))),
+        images_stripped: payload.exclude_images,
+        duplicate_of,
+        plain_text,
+        readability,
+        reading,
+        metadata,
+        formats,
+        cached: false,
+        scraped_at: Some(scraped_at),
+    })
+}
+
+// Whether a scrape sends credentials to the site
+fn is_authenticated(forwarded: &HashMap<String, String>) -> bool {
+    forwarded.contains_key("authorization")
+}
+
+// Handles `no_store` and authenticated scrapes: always fetched fresh, never
+// read from or written to the DB or OUTPUT_DIR.
+async fn scrape_without_storing(
+    state: &AppState,
+    payload: &ScrapeRequest,
+    upgraded_url: Option<String>,
+    accept_language: Option<&str>,
+    forwarded: &HashMap<String, String>,
+    extra_formats: &[ScrapeFormats],
+) -> Result<ScrapeResponse, AppError> {
+    info!("Scraping {} without storing", payload.url);
+    let (url, page, article) =
+        scrape_content(state, payload, upgraded_url, accept_language, forwarded, extra_formats).await?;
+    let readability = payload.readability.then_some(article.is_some());
+    let content = strip_images_if(payload.exclude_images, article.unwrap_or(page.markdown));
+    let mobile_content = if payload.mobile {
+        let mobile = scrape_markdown(state, &url, true, accept_language, forwarded).await?;
+        Some(wrap_if(payload.wrap_width, strip_images_if(payload.exclude_images, mobile)))
+    } else {
+        None
+    };
+
+    Ok(ScrapeResponse {
+        id: None,
+        url,
+        plain_text: payload.include_plain_text.then(|| markdown::to_plain_text(&content)),
+        reading: ReadingStats::of(&content),
+        content: wrap_if(payload.wrap_width, content),
+        mobile_content,
+        images_stripped: payload.exclude_images,
+        duplicate_of: None,
+        readability,
+        metadata: page.metadata,
+        formats: page.formats,
+        cached: false,
+        scraped_at: None,
+    })
+}
+
+// Finds the earliest item with the same content hash stored under a different URL.
+async fn find_duplicate(db: &DbPool, content_hash: &str, url: &str) -> Result<Option<i64>, AppError> {
+    let original = sqlx::query_scalar(
+        "SELECT id FROM scraped_items WHERE content_hash = $1 AND url != $2 AND deleted_at IS NULL ORDER BY id LIMIT 1",
+    )
+    .bind(content_hash)
+    .bind(url)
+    .fetch_optional(db)
+    .await?;
+    Ok(original)
+}
+
+// Returns an item's stored plain text, rendering and storing it first for
+// items scraped without `include_plain_text`.
+async fn get_or_store_plain_text(db: &DbPool, item_id: i64, content: &str) -> Result<String, AppError> {
+    let stored: Option<String> = sqlx::query_scalar("SELECT plain_text FROM scraped_items WHERE id = $1")
+        .bind(item_id)
+        .fetch_one(db)
+        .await?;
+    if let Some(plain_text) = stored {
+        return Ok(plain_text);
+    }
+
+    let plain_text = markdown::to_plain_text(content);
+    sqlx::query("UPDATE scraped_items SET plain_text = $1 WHERE id = $2")
+        .bind(&plain_text)
+        .bind(item_id)
+        .execute(db)
+        .await?;
+    info!("Stored plain text ({} bytes) for item {}", plain_text.len(), item_id);
+    Ok(plain_text)
+}
+
+fn strip_images_if(exclude_images: bool, content: String) -> String {
+    if exclude_images {
+        markdown::strip_images(&content)
+    } else {
+        content
+    }
+}
+
+fn wrap_if(wrap_width: Option<usize>, content: String) -> String {
+    match wrap_width {
+        Some(width) => markdown::wrap(&content, width),
+        None => content,
+    }
+}
+
+// `https://` form of an `http://` URL, or None when the URL isn't plain http.
+fn https_upgrade(url: &str) -> Option<String> {
+    let scheme_len = "http://".len();
+    url.get(..scheme_len)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("http://"))
+        .map(|_| format!("https://{}", &url[scheme_len..]))
+}
+
+// Plausibility check for an Accept-Language value: comma-separated language
+// tags (`en`, `pt-BR`, `zh-Hant-TW`, or `*`), each with an optional `;q=` weight.
+fn is_valid_accept_language(value: &str) -> bool {
+    value.len() <= 200
+        && value.split(',').all(|entry| {
+            let mut parts = entry.trim().split(';');
+            let tag = parts.next().unwrap_or("").trim();
+            let valid_tag = tag == "*"
+                || tag.split('-').enumerate().all(|(i, subtag)| {
+                    (1..=8).contains(&subtag.len())
+                        && if i == 0 {
+                            subtag.chars().all(|c| c.is_ascii_alphabetic())
+                        } else {
+                            subtag.chars().all(|c| c.is_ascii_alphanumeric())
+                        }
+                });
+            let valid_weight = parts.all(|param| {
+                param
+                    .trim()
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| (0.0..=1.0).contains(&q))
+            });
+            valid_tag && valid_weight
+        })
+}
+
+// Scrapes the requested page with Firecrawl. With `readability`, the page's
+// main article is also extracted when one can be found; it's returned as the
+// last element, next to the full page.
+async fn scrape_content(
+    state: &AppState,
+    payload: &ScrapeRequest,
+    upgraded: Option<String>,
+    accept_language: Option<&str>,
+    forwarded: &HashMap<String, String>,
+    extra_formats: &[ScrapeFormats],
+) -> Result<(String, ScrapedPage, Option<String>), AppError> {
+    let mut fetched_formats = extra_formats.to_vec();
+    if payload.readability && !fetched_formats.iter().any(|f| matches!(f, ScrapeFormats::RawHTML)) {
+        fetched_formats.push(ScrapeFormats::RawHTML);
+    }
+    let (url, mut page) =
+        scrape_preferring_https(state, &payload.url, upgraded, accept_language, forwarded, &fetched_formats).await?;
+    if !payload.readability {
+        return Ok((url, page, None));
+    }
+
+    let article = page
+        .formats
+        .raw_html
+        .as_deref()
+        .and_then(readability::extract_article)
+        .map(|article| absolutize_urls(&article, &url));
+    match &article {
+        Some(article) => info!("Extracted the main article of {} ({} bytes of Markdown)", url, article.len()),
+        None => info!("No article found in {}; falling back to Firecrawl's Markdown", url),
+    }
+    page.formats = page.formats.only(extra_formats);
+    Ok((url, page, article))
+}
+
+// Scrapes `upgraded` (the https:// form of `url`) when given, falling back to
+// `url` itself if that fails. Returns the URL that was actually scraped.
+async fn scrape_preferring_https(
+    state: &AppState,
+    url: &str,
+    upgraded: Option<String>,
+    accept_language: Option<&str>,
+    forwarded: &HashMap<String, String>,
+    extra_formats: &[ScrapeFormats],
+) -> Result<(String, ScrapedPage), AppError> {
+    if let Some(https_url) = upgraded {
+        match scrape_page(state, &https_url, false, accept_language, forwarded, extra_formats).await {
+            Ok(page) => return Ok((https_url, page)),
+            Err(AppError::Firecrawl(e)) => {
+                warn!("Scraping {} over https failed ({}); falling back to {}", https_url, e, url);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    let page = scrape_page(state, url, false, accept_language, forwarded, extra_formats).await?;
+    Ok((url.to_string(), page))
+}
+
+// What one Firecrawl scrape of a page returns
+struct ScrapedPage {
+    markdown: String,
+    formats: ExtraFormats,
+    metadata: PageMetadata,
+    options: Option<sqlx::types::Json<serde_json::Value>>, // The options it was scraped with; see `stored_options`
+}
+
+// The scrape options as stored with an item. Headers are left out, as
+// forwarded ones can carry cookies or tokens.
+fn stored_options(options: &ScrapeOptions) -> Option<sqlx::types::Json<serde_json::Value>> {
+    let options = ScrapeOptions { headers: None, ..options.clone() };
+    serde_json::to_value(&options).ok().map(sqlx::types::Json)
+}
+
+// Makes relative links in Markdown scraped from `url` absolute; see `markdown::absolutize_urls`
+fn absolutize_urls(markdown: &str, url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(base) => markdown::absolutize_urls(markdown, &base),
+        Err(_) => markdown.to_string(),
+    }
+}
+
+// Pages often send empty title/description tags; those count as missing
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+// Scrapes a single URL to Markdown only; see `scrape_page`
+async fn scrape_markdown(
+    state: &AppState,
+    url: &str,
+    mobile: bool,
+    accept_language: Option<&str>,
+    forwarded: &HashMap<String, String>,
+) -> Result<String, AppError> {
+    let page = scrape_page(state, url, mobile, accept_language, forwarded, &[]).await?;
+    Ok(page.markdown)
+}
+
+// Scrapes a single URL to Markdown plus any `extra_formats`, optionally
+// emulating a mobile viewport and asking for a specific language. `forwarded`
+// client headers are sent too, but the mobile User-Agent and `accept_language`
+// take precedence. Slow or failing calls are retried per `state.firecrawl_retry`.
+async fn scrape_page(
+    state: &AppState,
+    url: &str,
+    mobile: bool,
+    accept_language: Option<&str>,
+    forwarded: &HashMap<String, String>,
+    extra_formats: &[ScrapeFormats],
+) -> Result<ScrapedPage, AppError> {
+    let mut headers = forwarded.clone();
+    let authenticated = is_authenticated(&headers);
+    if mobile {
+        headers.insert("user-agent".to_string(), MOBILE_USER_AGENT.to_string());
+    }
+    if let Some(lang) = accept_language {
+        headers.insert("accept-language".to_string(), lang.to_string());
+    }
+    let headers = (!headers.is_empty()).then_some(headers);
     let scrape_options = ScrapeOptions {
-        formats: Some(vec![ScrapeFormats::Markdown]), // Request only Markdown
+        formats: Some([ScrapeFormats::Markdown].iter().chain(extra_formats).copied().collect()),
+        headers,
         ..Default::default()
     };
 
-    let scrape_result = state
-        .firecrawl_app
-        .scrape_url(&payload.url, Some(scrape_options))
-        .await?; // Use `?` to propagate FirecrawlError
+    let firecrawl_app = state.firecrawl()?;
+    let started = Instant::now();
+    let scrape_result = retry::with_retries(state.firecrawl_retry, url, || {
+        firecrawl_app.scrape_url(url, Some(scrape_options.clone()))
+    })
+    .await;
+    metrics::histogram!(telemetry::SCRAPE_DURATION_SECONDS).record(started.elapsed().as_secs_f64());
+    let scrape_result = scrape_result?;
+    if authenticated && scrape_result.metadata.status_code == 401 {
+        return Err(AppError::Unauthorized(format!("{} rejected the supplied credentials (HTTP 401)", url)));
+    }
 
     // Extract Markdown content
-    let markdown_content = scrape_result
+    let markdown = scrape_result
         .markdown
         .ok_or_else(|| AppError::Internal("Firecrawl did not return Markdown content".to_string()))?;
+    let markdown = absolutize_urls(&markdown, url);
+    let formats = ExtraFormats {
+        html: scrape_result.html,
+        raw_html: scrape_result.raw_html,
+        links: scrape_result.links.map(sqlx::types::Json),
+    }
+    .only(extra_formats);
+    let metadata = PageMetadata {
+        title: non_empty(scrape_result.metadata.title),
+        description: non_empty(scrape_result.metadata.description),
+    };
+    Ok(ScrapedPage { markdown, formats, metadata, options: stored_options(&scrape_options) })
+}
 
-    info!(
-        "Successfully scraped {} using Firecrawl ({} bytes of Markdown)",
-        payload.url,
-        markdown_content.len()
-    );
-
-    // 3. Insert Markdown content into database
-    let result = sqlx::query(
-        "INSERT INTO scraped_items (url, content) VALUES (?1, ?2)"
+// Returns the stored mobile variant for an item, scraping and storing it first if missing.
+async fn get_or_scrape_mobile_variant(
+    state: &AppState,
+    item_id: i64,
+    url: &str,
+    accept_language: Option<&str>,
+    forwarded: &HashMap<String, String>,
+) -> Result<String, AppError> {
+    let existing: Option<(String,)> = sqlx::query_as(
+        "SELECT content FROM scraped_variants WHERE item_id = $1 AND viewport = 'mobile'",
     )
-    .bind(&payload.url)
-    .bind(&markdown_content) // Store Markdown content
-    .execute(&state.db)
+    .bind(item_id)
+    .fetch_optional(&state.db)
     .await?;
 
-    let new_id = result.last_insert_rowid();
-    info!("Successfully inserted Markdown for URL {} with ID {}", payload.url, new_id);
+    if let Some((content,)) = existing {
+        info!("Mobile variant for item {} found in database", item_id);
+        return Ok(content);
+    }
 
-    // Return the newly scraped Markdown content
-    Ok(Json(ScrapeResponse {
-        id: new_id,
-        url: payload.url,
-        content: markdown_content,
-    }))
+    info!("Scraping mobile variant of {} with Firecrawl...", url);
+    let content = scrape_markdown(state, url, true, accept_language, forwarded)
+        .await
+        .map_err(|e| match e {
+            AppError::Firecrawl(err) => {
+                AppError::Internal(format!("Mobile viewport scrape failed for {}: {}", url, err))
+            }
+            other => other,
+        })?;
+
+    sqlx::query("INSERT INTO scraped_variants (item_id, viewport, content) VALUES ($1, 'mobile', $2)")
+        .bind(item_id)
+        .bind(&content)
+        .execute(&state.db)
+        .await?;
+    info!("Stored mobile variant ({} bytes) for item {}", content.len(), item_id);
+
+    Ok(content)
 }
 
 #[instrument(skip(state))]
 async fn get_history_handler(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<HistoryQuery>,
 ) -> Result<Json<Vec<ScrapedItem>>, AppError> {
     info!("Fetching scrape history");
-    let items = sqlx::query_as::<_, ScrapedItem>("SELECT id, url, content, created_at FROM scraped_items ORDER BY created_at DESC")
-        .fetch_all(&state.db)
-        .await?;
+    let tag = query.tag.map(|tag| tag.trim().to_lowercase());
+    let mut items = sqlx::query_as::<_, ScrapedItem>(&format!(
+        "SELECT {} FROM scraped_items WHERE (deleted_at IS NULL OR $2)
+             AND ($1 IS NULL OR id IN (SELECT item_id FROM item_tags WHERE tag = $1))
+         ORDER BY created_at DESC",
+        ITEM_COLUMNS
+    ))
+    .bind(tag)
+    .bind(query.include_trashed)
+    .fetch_all(&state.db)
+    .await?;
+    tags::attach_tags(&state.db, &mut items).await?;
     info!("Found {} items in history", items.len());
     Ok(Json(items))
 }
@@ -274,12 +1331,174 @@ async fn get_history_handler(
 async fn get_item_handler(
     State(state): State<Arc<AppState>>,
     Path(id): Path<i64>,
-) -> Result<Json<ScrapedItem>, AppError> {
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
     info!("Fetching scraped item with ID: {}", id);
-    let item = sqlx::query_as::<_, ScrapedItem>("SELECT id, url, content, created_at FROM scraped_items WHERE id = ?1")
+    let mut item = sqlx::query_as::<_, ScrapedItem>(&format!("SELECT {} FROM scraped_items WHERE id = $1 AND deleted_at IS NULL", ITEM_COLUMNS))
         .bind(id)
         .fetch_one(&state.db) // Use fetch_one to get a specific item or error if not found
         .await?; // Automatically converts RowNotFound to AppError::NotFound via From trait
     info!("Found item with ID: {}", item.id);
-    Ok(Json(item))
-} 
\ No newline at end of file
+    tags::attach_tags(&state.db, std::slice::from_mut(&mut item)).await?;
+    let mut response = match ItemFormat::from_accept(&headers) {
+        ItemFormat::Json => Json(item).into_response(),
+        ItemFormat::Markdown => ([(header::CONTENT_TYPE, "text/markdown; charset=utf-8")], item.content).into_response(),
+        ItemFormat::Html => {
+            let title = item.metadata.title.as_deref().unwrap_or(&item.url);
+            let page = markdown::to_html(title, &item.content);
+            // The content comes from arbitrary sites; keep the page from running or loading scripts
+            let headers = [
+                (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+                (header::CONTENT_SECURITY_POLICY, "default-src 'none'; img-src * data:; style-src 'unsafe-inline'"),
+            ];
+            (headers, page).into_response()
+        }
+    };
+    response.headers_mut().append(header::VARY, HeaderValue::from_static("accept"));
+    Ok(response)
+}
+
+// Representations of an item GET /history/:id can answer with
+#[derive(Debug, PartialEq)]
+enum ItemFormat {
+    Json,
+    Markdown, // Just the content
+    Html, // The content rendered as a page
+}
+
+impl ItemFormat {
+    // The supported type `Accept` gives the highest quality value, the one
+    // listed first among equals. Each type gets the q of the most specific range
+    // matching it (`text/html`, then `text/*`, then `*/*`). JSON when none is
+    // acceptable, including when there's no header.
+    fn from_accept(headers: &HeaderMap) -> Self {
+        let accept = headers.get(header::ACCEPT).and_then(|value| value.to_str().ok()).unwrap_or_default();
+        let ranges: Vec<(String, f32)> = accept
+            .split(',')
+            .map(|range| {
+                let mut parts = range.split(';');
+                let media_range = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+                let q = parts
+                    .filter_map(|param| param.split_once('='))
+                    .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                    .and_then(|(_, value)| value.trim().parse::<f32>().ok())
+                    .map_or(1.0, |q| q.clamp(0.0, 1.0));
+                (media_range, q)
+            })
+            .collect();
+
+        [
+            (ItemFormat::Json, "application/json"),
+            (ItemFormat::Html, "text/html"),
+            (ItemFormat::Markdown, "text/markdown"),
+        ]
+        .into_iter()
+        .filter_map(|(format, media_type)| {
+            let main_type = media_type.split('/').next().unwrap_or_default();
+            let (_, position, q) = ranges
+                .iter()
+                .enumerate()
+                .filter_map(|(position, (range, q))| {
+                    let specificity = if range == media_type {
+                        3
+                    } else if range.strip_suffix("/*") == Some(main_type) {
+                        2
+                    } else if range == "*/*" {
+                        1
+                    } else {
+                        return None;
+                    };
+                    Some((specificity, position, *q))
+                })
+                .max_by_key(|(specificity, ..)| *specificity)?;
+            (q > 0.0).then_some((format, position, q))
+        })
+        // min_by keeps the first of equals, so JSON wins a tie on `*/*`
+        .min_by(|(_, a_position, a_q), (_, b_position, b_q)| b_q.total_cmp(a_q).then(a_position.cmp(b_position)))
+        .map_or(ItemFormat::Json, |(format, ..)| format)
+    }
+}
+
+#[instrument(skip(state))]
+async fn purge_cache_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PurgeCacheQuery>,
+) -> Result<Json<PurgeCacheResponse>, AppError> {
+    let candidates = cache_url_candidates(&query.url, state.upgrade_http);
+    info!("Purging cache entry for {} (matching {:?})", query.url, candidates);
+
+    // Variants are removed with their item via ON DELETE CASCADE
+    let mut delete = QueryBuilder::<Db>::new("DELETE FROM scraped_items WHERE url IN (");
+    let mut urls = delete.separated(", ");
+    for candidate in &candidates {
+        urls.push_bind(candidate);
+    }
+    delete.push(")");
+    let removed = delete.build().execute(&state.db).await?.rows_affected() > 0;
+
+    info!("Cache entry for {} removed: {}", query.url, removed);
+    Ok(Json(PurgeCacheResponse {
+        url: query.url,
+        removed,
+    }))
+}
+
+// The stored URLs a requested URL may be cached under: the URL as given and its
+// normalized form (lowercased scheme/host, no fragment), each with and without a
+// trailing slash, plus their https:// forms when /scrape upgrades http URLs.
+fn cache_url_candidates(url: &str, upgrade_http: bool) -> Vec<String> {
+    let url = url.trim();
+    let mut bases = vec![url.to_string()];
+    if let Ok(mut parsed) = reqwest::Url::parse(url) {
+        parsed.set_fragment(None);
+        bases.push(parsed.to_string());
+    }
+    if upgrade_http {
+        let upgraded: Vec<String> = bases.iter().filter_map(|base| https_upgrade(base)).collect();
+        bases.extend(upgraded);
+    }
+
+    let mut candidates = Vec::new();
+    for base in bases {
+        let trimmed = base.trim_end_matches('/').to_string();
+        for candidate in [format!("{}/", trimmed), trimmed] {
+            if !candidates.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format_for(accept: &str) -> ItemFormat {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_str(accept).unwrap());
+        ItemFormat::from_accept(&headers)
+    }
+
+    #[test]
+    fn the_highest_quality_value_wins() {
+        assert_eq!(format_for("text/html;q=0.1, application/json"), ItemFormat::Json);
+        assert_eq!(format_for("application/json;q=0.5, text/markdown;q=0.8"), ItemFormat::Markdown);
+        assert_eq!(format_for("text/html, text/markdown"), ItemFormat::Html);
+    }
+
+    #[test]
+    fn wildcards_fall_back_to_json() {
+        assert_eq!(format_for("*/*"), ItemFormat::Json);
+        assert_eq!(format_for("text/markdown;q=0.5, */*;q=0.9"), ItemFormat::Json);
+        assert_eq!(format_for("text/*, application/json;q=0.5"), ItemFormat::Html);
+        assert_eq!(ItemFormat::from_accept(&HeaderMap::new()), ItemFormat::Json);
+    }
+
+    #[test]
+    fn types_with_a_zero_quality_value_are_not_acceptable() {
+        assert_eq!(format_for("application/json;q=0, text/markdown"), ItemFormat::Markdown);
+        assert_eq!(format_for("*/*, application/json;q=0"), ItemFormat::Html);
+        assert_eq!(format_for("text/html;q=0"), ItemFormat::Json);
+    }
+}
@@ -0,0 +1,211 @@
+// Post-processing helpers for scraped Markdown.
+
+use pulldown_cmark::{html, Event, LinkType, Options, Parser, Tag, TagEnd};
+use reqwest::Url;
+use ruscraper_markdown::matching_close;
+use sha2::{Digest, Sha256};
+use std::ops::Range;
+
+pub use ruscraper_markdown::wrap;
+
+/// Hex-encoded SHA-256 of the content, used to spot identical pages under different URLs.
+pub fn content_hash(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Removes image references from Markdown: inline `![alt](src)` images,
+/// reference-style `![alt][ref]` images, and raw HTML `<img>` tags.
+pub fn strip_images(markdown: &str) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut rest = markdown;
+
+    while let Some(pos) = next_image_start(rest) {
+        out.push_str(&rest[..pos]);
+        let candidate = &rest[pos..];
+        match image_len(candidate) {
+            Some(len) => rest = &candidate[len..],
+            None => {
+                // Not actually an image; keep the first character and move on
+                let ch_len = candidate.chars().next().map_or(1, char::len_utf8);
+                out.push_str(&candidate[..ch_len]);
+                rest = &candidate[ch_len..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// Finds the next position that might start an image (`![` or `<img`).
+fn next_image_start(text: &str) -> Option<usize> {
+    let markdown = text.find("![");
+    let html = find_ascii_case_insensitive(text, "<img");
+    match (markdown, html) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+// Returns the byte length of the image syntax at the start of `text`, if any.
+fn image_len(text: &str) -> Option<usize> {
+    if text.starts_with("![") {
+        let alt_end = matching_close(text, 1, '[', ']')?;
+        match text[alt_end + 1..].chars().next() {
+            Some('(') => matching_close(text, alt_end + 1, '(', ')').map(|end| end + 1),
+            Some('[') => matching_close(text, alt_end + 1, '[', ']').map(|end| end + 1),
+            _ => None,
+        }
+    } else {
+        // `<img ...>`: require a tag boundary so `<imgfoo>` isn't matched
+        let after = text[4..].chars().next()?;
+        if !(after.is_whitespace() || after == '/' || after == '>') {
+            return None;
+        }
+        text.find('>').map(|end| end + 1)
+    }
+}
+
+fn find_ascii_case_insensitive(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/// Rewrites relative link and image destinations against `base`, the URL the
+/// page was scraped from, so they still work once the Markdown is shown
+/// elsewhere. Inline links and images and reference definitions are covered.
+/// Protocol-relative `//host/path` destinations take `base`'s scheme. Absolute
+/// URLs (`mailto:` and the like included) and in-page `#fragment` links are
+/// left alone, as are destinations written with escapes or padded with spaces.
+pub fn absolutize_urls(markdown: &str, base: &Url) -> String {
+    let parser = Parser::new_ext(markdown, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH);
+    let mut edits: Vec<(Range<usize>, String)> = parser
+        .reference_definitions()
+        .iter()
+        .filter_map(|(_, def)| {
+            // The destination follows the `[label]:`
+            let label_end = markdown[def.span.clone()].find("]:")? + def.span.start + 2;
+            locate_destination(markdown, label_end..def.span.end, &def.dest, base)
+        })
+        .collect();
+    for (event, range) in parser.into_offset_iter() {
+        if let Event::Start(
+            Tag::Link { link_type: LinkType::Inline, dest_url, .. } | Tag::Image { link_type: LinkType::Inline, dest_url, .. },
+        ) = event
+        {
+            // The destination opens the final `(...)` of the link
+            let Some(open) = markdown[range.clone()].rfind(&format!("({}", dest_url)) else { continue };
+            edits.extend(locate_destination(markdown, range.start + open..range.end, &dest_url, base));
+        }
+    }
+
+    // Edits are applied in source order; nested links never share a destination
+    edits.sort_by_key(|(range, _)| range.start);
+    let mut out = String::with_capacity(markdown.len());
+    let mut copied = 0;
+    for (range, url) in edits {
+        if range.start < copied {
+            continue;
+        }
+        out.push_str(&markdown[copied..range.start]);
+        out.push_str(&url);
+        copied = range.end;
+    }
+    out.push_str(&markdown[copied..]);
+    out
+}
+
+// Finds `dest` within `within` and returns its range with the absolute URL to
+// put there, or None when `dest` isn't relative.
+fn locate_destination(markdown: &str, within: Range<usize>, dest: &str, base: &Url) -> Option<(Range<usize>, String)> {
+    if dest.is_empty() || dest.starts_with('#') || Url::parse(dest).is_ok() {
+        return None;
+    }
+    let absolute = base.join(dest).ok()?;
+    let start = within.start + markdown[within].find(dest)?;
+    Some((start..start + dest.len(), absolute.to_string()))
+}
+
+/// Words in the readable text of `markdown`, i.e. of its plain-text rendering.
+pub fn word_count(markdown: &str) -> u32 {
+    to_plain_text(markdown).split_whitespace().count() as u32
+}
+
+/// Plain-text rendering of Markdown: emphasis, heading markers, link targets,
+/// images and raw HTML are dropped, leaving the readable text. Blocks are
+/// separated by blank lines, list items and table rows by line breaks, and
+/// table cells by tabs.
+pub fn to_plain_text(markdown: &str) -> String {
+    let parser = Parser::new_ext(markdown, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH);
+    let mut out = String::with_capacity(markdown.len());
+    let mut image_depth = 0usize; // Alt text of images is skipped along with the image
+
+    for event in parser {
+        match event {
+            Event::Start(Tag::Image { .. }) => image_depth += 1,
+            Event::End(TagEnd::Image) => image_depth -= 1,
+            _ if image_depth > 0 => {}
+            Event::Text(text) | Event::Code(text) => out.push_str(&text),
+            Event::SoftBreak => out.push(' '),
+            Event::HardBreak => end_line(&mut out, false),
+            Event::End(TagEnd::TableCell) => out.push('\t'),
+            Event::End(TagEnd::Item | TagEnd::TableHead | TagEnd::TableRow) => end_line(&mut out, false),
+            Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Heading(_)
+                | TagEnd::CodeBlock
+                | TagEnd::BlockQuote(_)
+                | TagEnd::List(_)
+                | TagEnd::Table,
+            ) => end_line(&mut out, true),
+            _ => {}
+        }
+    }
+    out.trim().to_string()
+}
+
+/// A standalone HTML page showing `markdown`, for viewing an item in a
+/// browser. Scraped content isn't trusted: raw HTML in it is shown as text and
+/// `javascript:` links and images lose their target.
+pub fn to_html(title: &str, markdown: &str) -> String {
+    let parser = Parser::new_ext(markdown, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH).map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        Event::Start(Tag::Link { link_type, dest_url, title, id }) if is_script_url(&dest_url) => {
+            Event::Start(Tag::Link { link_type, dest_url: "".into(), title, id })
+        }
+        Event::Start(Tag::Image { link_type, dest_url, title, id }) if is_script_url(&dest_url) => {
+            Event::Start(Tag::Image { link_type, dest_url: "".into(), title, id })
+        }
+        other => other,
+    });
+    let mut out = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>");
+    html::push_html(&mut out, std::iter::once(Event::Text(title.into())));
+    out.push_str("</title>\n</head>\n<body>\n");
+    html::push_html(&mut out, parser);
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn is_script_url(url: &str) -> bool {
+    let scheme = url.trim_start().split(':').next().unwrap_or_default();
+    url.contains(':') && (scheme.eq_ignore_ascii_case("javascript") || scheme.eq_ignore_ascii_case("vbscript"))
+}
+
+// Ends the current line (and leaves a blank one after it when `blank`),
+// without stacking up empty lines.
+fn end_line(out: &mut String, blank: bool) {
+    let trimmed = out.trim_end_matches([' ', '\t']).len();
+    out.truncate(trimmed);
+    if out.is_empty() {
+        return;
+    }
+    let wanted = if blank { 2 } else { 1 };
+    let newlines = out.len() - out.trim_end_matches('\n').len();
+    for _ in newlines..wanted {
+        out.push('\n');
+    }
+}
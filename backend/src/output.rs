@@ -0,0 +1,58 @@
+use std::path::Path;
+use tracing::{error, info};
+
+// Keeps generated filenames well under common filesystem limits
+const MAX_FILENAME_LEN: usize = 150;
+
+/// Writes a scraped page to `<dir>/<sanitized-url>.md`, or to
+/// `<sanitized-url>-<id>.md` when that name is taken by another URL. A page
+/// scraped again overwrites its own file. The first line records the URL, as
+/// an HTML comment that doesn't show when the Markdown is rendered. Failures
+/// are logged, never propagated: the file copy is a convenience and must not
+/// fail the scrape.
+pub async fn write_markdown_file(dir: &Path, url: &str, id: i64, content: &str) {
+    if let Err(e) = tokio::fs::create_dir_all(dir).await {
+        error!("Failed to create OUTPUT_DIR {}: {}", dir.display(), e);
+        return;
+    }
+
+    let stem = sanitize_url(url);
+    let header = source_line(url);
+    let mut path = dir.join(format!("{}.md", stem));
+    // Different URLs can sanitize to the same name, e.g. http:// and https://
+    match tokio::fs::read_to_string(&path).await {
+        Ok(existing) if existing.lines().next() == Some(header.as_str()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        _ => path = dir.join(format!("{}-{}.md", stem, id)),
+    }
+
+    match tokio::fs::write(&path, format!("{}\n\n{}", header, content)).await {
+        Ok(()) => info!("Wrote Markdown for {} to {}", url, path.display()),
+        Err(e) => error!("Failed to write Markdown file {}: {}", path.display(), e),
+    }
+}
+
+// First line of a page's file, naming the URL it was scraped from. `--` can't
+// appear inside an HTML comment, so it is escaped.
+fn source_line(url: &str) -> String {
+    format!("<!-- source: {} -->", url.replace("--", "%2D%2D"))
+}
+
+// Turns a URL into a filesystem-safe name, e.g. `https://a.com/b?c=1` -> `a.com_b_c_1`.
+fn sanitize_url(url: &str) -> String {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let mut name = String::with_capacity(without_scheme.len());
+    for c in without_scheme.chars() {
+        if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+            name.push(c);
+        } else if !name.ends_with('_') {
+            name.push('_');
+        }
+    }
+    let name: String = name.trim_matches(|c| c == '_' || c == '.').chars().take(MAX_FILENAME_LEN).collect();
+    if name.is_empty() {
+        "page".to_string()
+    } else {
+        name
+    }
+}
@@ -0,0 +1,127 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::ErrorResponse;
+
+// Past this many tracked clients, buckets that have refilled completely are dropped
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Token buckets per client IP: each holds `per_minute` tokens and refills
+/// continuously, so bursts up to the limit are fine but the sustained rate
+/// is capped. Configured via SCRAPE_RATE_LIMIT; 0 disables limiting.
+pub struct RateLimiter {
+    per_minute: u32,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(per_minute: u32) -> Self {
+        Self {
+            per_minute,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn per_minute(&self) -> u32 {
+        self.per_minute
+    }
+
+    /// Takes a token for `ip`, or returns how long until one is available.
+    pub fn check(&self, ip: IpAddr) -> Result<(), Duration> {
+        if self.per_minute == 0 {
+            return Ok(());
+        }
+        let capacity = self.per_minute as f64;
+        let per_second = capacity / 60.0;
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() > MAX_TRACKED_CLIENTS {
+            buckets.retain(|_, bucket| bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * per_second < capacity);
+        }
+
+        let bucket = buckets.entry(ip).or_insert(Bucket { tokens: capacity, updated: now });
+        bucket.tokens = (bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * per_second).min(capacity);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+        }
+    }
+}
+
+/// Middleware answering 429 with `Retry-After` once a client IP is out of tokens.
+pub async fn limit_by_ip(
+    State(limiter): State<Arc<RateLimiter>>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Err(wait) = limiter.check(client.ip()) {
+        let retry_after = wait.as_secs_f64().ceil() as u64;
+        warn!("Rate limited {} on {}; retry in {}s", client.ip(), request.uri().path(), retry_after);
+        let body = Json(ErrorResponse {
+            code: "RATE_LIMITED",
+            message: format!(
+                "Rate limit of {} scrapes per minute exceeded; retry in {}s",
+                limiter.per_minute(),
+                retry_after
+            ),
+        });
+        return (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, retry_after.to_string())], body).into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, extract::connect_info::MockConnectInfo, middleware, routing::post, Router};
+    use tower::ServiceExt;
+
+    const LIMIT: u32 = 10;
+
+    fn app(limiter: Arc<RateLimiter>, client: [u8; 4]) -> Router {
+        Router::new()
+            .route("/scrape", post(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(limiter, limit_by_ip))
+            .layer(MockConnectInfo(SocketAddr::from((client, 40000))))
+    }
+
+    async fn scrape(limiter: &Arc<RateLimiter>, client: [u8; 4]) -> Response {
+        let request = Request::post("/scrape").body(Body::empty()).unwrap();
+        app(limiter.clone(), client).oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn the_request_after_the_limit_is_rejected() {
+        let limiter = Arc::new(RateLimiter::new(LIMIT));
+        for _ in 0..LIMIT {
+            assert_eq!(scrape(&limiter, [10, 0, 0, 1]).await.status(), StatusCode::OK);
+        }
+
+        let response = scrape(&limiter, [10, 0, 0, 1]).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = response.headers()[header::RETRY_AFTER].to_str().unwrap().parse().unwrap();
+        assert!((1..=6).contains(&retry_after), "Retry-After was {}", retry_after);
+
+        // Other clients have their own budget
+        assert_eq!(scrape(&limiter, [10, 0, 0, 2]).await.status(), StatusCode::OK);
+    }
+}
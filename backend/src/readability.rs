@@ -0,0 +1,114 @@
+// Reader-view style article extraction: finds the element holding the page's
+// main text and converts just that to Markdown. A simplified take on the
+// scoring in Mozilla's Readability.
+
+use ruscraper_markdown::html::Converter;
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashMap;
+
+// Never part of an article
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "nav", "aside", "footer", "form", "iframe", "button", "svg", "select", "input",
+];
+// Class/id fragments that mark page furniture rather than content
+const NEGATIVE_HINTS: &[&str] = &[
+    "comment", "footer", "nav", "sidebar", "menu", "share", "social", "related", "promo", "banner", "advert", "cookie",
+    "popup", "subscribe",
+];
+const POSITIVE_HINTS: &[&str] = &["article", "body", "content", "entry", "main", "post", "text", "story"];
+
+// Paragraphs shorter than this (in characters) don't count towards a score
+const MIN_PARAGRAPH_CHARS: usize = 25;
+// Below these the page isn't considered to have an article
+const MIN_ARTICLE_SCORE: f64 = 20.0;
+const MIN_ARTICLE_CHARS: usize = 250;
+
+/// The page's main article as Markdown, or None when no element stands out
+/// as one (index pages, very short pages).
+pub fn extract_article(html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let paragraphs = Selector::parse("p, pre, td, blockquote").ok()?;
+
+    // Paragraph text scores its parent fully and its grandparent by half
+    let mut scores = HashMap::new();
+    for paragraph in document.select(&paragraphs) {
+        if paragraph.ancestors().filter_map(ElementRef::wrap).any(is_skipped) {
+            continue;
+        }
+        let text: String = paragraph.text().collect();
+        let length = text.trim().chars().count();
+        if length < MIN_PARAGRAPH_CHARS {
+            continue;
+        }
+        let score = 1.0 + text.matches(',').count() as f64 + (length as f64 / 100.0).min(3.0);
+        let mut ancestors = paragraph.ancestors().filter_map(ElementRef::wrap);
+        for share in [1.0, 0.5] {
+            let Some(ancestor) = ancestors.next() else { break };
+            *scores.entry(ancestor.id()).or_insert_with(|| initial_score(ancestor)) += score * share;
+        }
+    }
+
+    let (best, score) = scores
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let element = ElementRef::wrap(document.tree.get(id)?)?;
+            Some((element, score * (1.0 - link_density(element))))
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+    if score < MIN_ARTICLE_SCORE {
+        return None;
+    }
+    let skip = |element: ElementRef<'_>| is_skipped(element) || is_unlikely(element);
+    let markdown = Converter::default().skipping(&skip).block_markdown(best);
+    (markdown.chars().count() >= MIN_ARTICLE_CHARS).then_some(markdown)
+}
+
+// Score a candidate starts with, from its tag and class/id
+fn initial_score(element: ElementRef<'_>) -> f64 {
+    let tag_score = match element.value().name() {
+        "article" => 10.0,
+        "div" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "form" | "ol" | "ul" | "dl" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+    tag_score + class_weight(element)
+}
+
+fn class_weight(element: ElementRef<'_>) -> f64 {
+    let hints = format!(
+        "{} {}",
+        element.value().attr("class").unwrap_or_default(),
+        element.value().id().unwrap_or_default()
+    )
+    .to_ascii_lowercase();
+    let mut weight = 0.0;
+    if NEGATIVE_HINTS.iter().any(|hint| hints.contains(hint)) {
+        weight -= 25.0;
+    }
+    if POSITIVE_HINTS.iter().any(|hint| hints.contains(hint)) {
+        weight += 25.0;
+    }
+    weight
+}
+
+// Share of an element's text that sits inside links
+fn link_density(element: ElementRef<'_>) -> f64 {
+    let total: usize = element.text().map(str::len).sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let links = Selector::parse("a").expect("valid selector");
+    let linked: usize = element.select(&links).flat_map(|link| link.text()).map(str::len).sum();
+    linked as f64 / total as f64
+}
+
+fn is_skipped(element: ElementRef<'_>) -> bool {
+    SKIPPED_ELEMENTS.contains(&element.value().name())
+}
+
+// Furniture inside the article itself, like share bars and comment sections
+fn is_unlikely(element: ElementRef<'_>) -> bool {
+    class_weight(element) < 0.0
+}
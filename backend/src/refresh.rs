@@ -0,0 +1,98 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{info, instrument, warn};
+
+use crate::{run_scrape, telemetry, AppError, AppState, ScrapeRequest};
+
+// Items re-scraped at once; matches the DB pool size
+const REFRESH_CONCURRENCY: usize = 5;
+
+#[derive(Deserialize, Debug)]
+pub struct RefreshQuery {
+    older_than_days: u32,
+}
+
+#[derive(Serialize)]
+pub struct RefreshResponse {
+    refreshed: usize,
+    failed: usize,
+}
+
+// What an item was scraped with, so the refresh asks for the same again
+#[derive(sqlx::FromRow)]
+struct StaleItem {
+    url: String,
+    accept_language: Option<String>,
+    images_stripped: bool,
+    readability: bool,
+    html: bool, // Extra formats the item has stored
+    raw_html: bool,
+    links: bool,
+}
+
+impl StaleItem {
+    fn formats(&self) -> Vec<String> {
+        [(self.html, "html"), (self.raw_html, "rawHtml"), (self.links, "links")]
+            .into_iter()
+            .filter(|(stored, _)| *stored)
+            .map(|(_, name)| name.to_string())
+            .collect()
+    }
+}
+
+/// Re-scrapes every item scraped more than `older_than_days` ago, a few at a
+/// time, like `POST /scrape` with `force` would. Items keep their id and get a
+/// new `created_at`; one that fails to scrape keeps its old content.
+#[instrument(skip(state))]
+pub async fn refresh_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RefreshQuery>,
+) -> Result<Json<RefreshResponse>, AppError> {
+    let items = sqlx::query_as::<_, StaleItem>(
+        "SELECT url, accept_language, images_stripped, readability IS NOT NULL AS readability,
+                html IS NOT NULL AS html, raw_html IS NOT NULL AS raw_html, links IS NOT NULL AS links
+         FROM scraped_items
+         WHERE deleted_at IS NULL AND created_at < datetime('now', $1)
+         ORDER BY created_at",
+    )
+    .bind(format!("-{} days", query.older_than_days))
+    .fetch_all(&state.db)
+    .await?;
+    info!("Refreshing {} items older than {} days", items.len(), query.older_than_days);
+    metrics::counter!(telemetry::SCRAPES_TOTAL).increment(items.len() as u64);
+
+    let forwarded = HashMap::new();
+    let results: Vec<bool> = stream::iter(items)
+        .map(|item| {
+            let state = &state;
+            let forwarded = &forwarded;
+            async move {
+                let request = ScrapeRequest {
+                    formats: Some(item.formats()),
+                    url: item.url,
+                    exclude_images: item.images_stripped,
+                    force: true,
+                    readability: item.readability,
+                    ..Default::default()
+                };
+                run_scrape(state, &request, item.accept_language.as_deref(), forwarded)
+                    .await
+                    .inspect_err(|e| warn!("Refresh of {} failed: {}", request.url, e))
+                    .is_ok()
+            }
+        })
+        .buffer_unordered(REFRESH_CONCURRENCY)
+        .collect()
+        .await;
+
+    let refreshed = results.iter().filter(|ok| **ok).count();
+    let failed = results.len() - refreshed;
+    info!("Refresh finished: {} refreshed, {} failed", refreshed, failed);
+    Ok(Json(RefreshResponse { refreshed, failed }))
+}
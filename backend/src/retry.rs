@@ -0,0 +1,160 @@
+use firecrawl::FirecrawlError;
+use std::future::Future;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::{env_parse_or, AppError};
+
+// Tries per Firecrawl call, the first one included
+const ATTEMPTS: u32 = 3;
+
+// Delay before the first retry; doubles per attempt
+const BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Timeout and retries for Firecrawl calls. The timeout is overridable via
+/// FIRECRAWL_TIMEOUT_SECS.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    pub timeout: Duration, // Per attempt
+    pub attempts: u32,
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_env() -> Self {
+        Self {
+            timeout: Duration::from_secs(env_parse_or("FIRECRAWL_TIMEOUT_SECS", 30u64).max(1)),
+            attempts: ATTEMPTS,
+            base_delay: BASE_DELAY,
+        }
+    }
+}
+
+/// Whether a Firecrawl failure may go away on its own: network trouble, rate
+/// limiting (429) and server errors (5xx). Anything else, such as a rejected
+/// URL or an exhausted plan, fails the same way every time.
+pub fn is_transient(error: &FirecrawlError) -> bool {
+    match error {
+        FirecrawlError::HttpError(..) | FirecrawlError::ResponseParseErrorText(_) => true,
+        FirecrawlError::HttpRequestFailed(_, status, _) => *status == 429 || *status >= 500,
+        _ => false,
+    }
+}
+
+/// Runs `call` with the policy's timeout, retrying timeouts and transient
+/// Firecrawl failures (see `is_transient`) with exponential backoff. Other
+/// failures are returned right away. When every attempt fails, the last
+/// failure is returned: `AppError::Timeout` if it timed out, otherwise the
+/// Firecrawl error.
+pub async fn with_retries<T, F, Fut>(policy: RetryPolicy, url: &str, mut call: F) -> Result<T, AppError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, FirecrawlError>>,
+{
+    let mut attempt = 1;
+    loop {
+        let error = match tokio::time::timeout(policy.timeout, call()).await {
+            Ok(Ok(value)) => return Ok(value),
+            Ok(Err(e)) if is_transient(&e) => AppError::Firecrawl(e),
+            Ok(Err(e)) => return Err(AppError::Firecrawl(e)),
+            Err(_) => AppError::Timeout(format!(
+                "Firecrawl did not respond within {}s for {}",
+                policy.timeout.as_secs(),
+                url
+            )),
+        };
+        if attempt >= policy.attempts {
+            return Err(error);
+        }
+        let delay = policy.base_delay * 2u32.pow(attempt - 1);
+        warn!(
+            "Firecrawl attempt {}/{} for {} failed ({}); retrying in {:?}",
+            attempt, policy.attempts, url, error, delay
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+
+    fn test_policy() -> RetryPolicy {
+        RetryPolicy {
+            timeout: Duration::from_millis(20),
+            attempts: ATTEMPTS,
+            base_delay: Duration::from_millis(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn a_call_that_always_times_out_is_tried_three_times() {
+        let mut attempts = 0;
+        let result: Result<(), AppError> = with_retries(test_policy(), "https://example.com", || {
+            attempts += 1;
+            std::future::pending()
+        })
+        .await;
+
+        assert_eq!(attempts, 3);
+        let error = result.unwrap_err();
+        assert!(matches!(error, AppError::Timeout(_)));
+        assert_eq!(error.into_response().status(), StatusCode::GATEWAY_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn a_permanent_firecrawl_error_is_not_retried() {
+        let mut attempts = 0;
+        let result: Result<(), AppError> = with_retries(test_policy(), "https://example.com", || {
+            attempts += 1;
+            std::future::ready(Err(FirecrawlError::HttpRequestFailed(
+                "Scraping URL".to_string(),
+                402,
+                "Payment required".to_string(),
+            )))
+        })
+        .await;
+
+        assert_eq!(attempts, 1);
+        assert!(matches!(result.unwrap_err(), AppError::Firecrawl(_)));
+    }
+
+    #[tokio::test]
+    async fn a_firecrawl_server_error_is_retried() {
+        let mut attempts = 0;
+        let result: Result<(), AppError> = with_retries(test_policy(), "https://example.com", || {
+            attempts += 1;
+            std::future::ready(Err(FirecrawlError::HttpRequestFailed(
+                "Scraping URL".to_string(),
+                503,
+                "Service unavailable".to_string(),
+            )))
+        })
+        .await;
+
+        assert_eq!(attempts, 3);
+        assert!(matches!(result.unwrap_err(), AppError::Firecrawl(_)));
+    }
+
+    #[tokio::test]
+    async fn retrying_stops_at_the_first_success() {
+        let mut attempts = 0;
+        let result = with_retries(test_policy(), "https://example.com", || {
+            attempts += 1;
+            let timed_out = attempts == 1;
+            async move {
+                if timed_out {
+                    std::future::pending::<()>().await;
+                }
+                Ok("markdown")
+            }
+        })
+        .await;
+
+        assert_eq!(attempts, 2);
+        assert_eq!(result.unwrap(), "markdown");
+    }
+}
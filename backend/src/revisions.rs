@@ -0,0 +1,109 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use std::sync::Arc;
+use tracing::{info, instrument};
+
+use crate::{db::DbPool, AppError, AppState};
+
+// Unchanged lines shown around each change in the unified diff
+const CONTEXT_LINES: usize = 3;
+
+/// An earlier version of an item's content, saved when the item was re-scraped.
+#[derive(Serialize, sqlx::FromRow)]
+pub struct Revision {
+    id: i64,
+    scraped_at: Option<String>,
+    replaced_at: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DiffQuery {
+    against: i64, // Revision to compare the item's current content with
+}
+
+#[derive(Serialize)]
+pub struct DiffResponse {
+    id: i64,
+    against: i64,
+    url: String,
+    added: usize, // Lines only in the current content
+    removed: usize, // Lines only in the revision
+    diff: String, // Unified diff from the revision to the current content
+}
+
+/// Lists an item's earlier revisions, newest first.
+#[instrument(skip(state))]
+pub async fn list_revisions_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Revision>>, AppError> {
+    current_content(&state.db, id).await?;
+    let revisions = sqlx::query_as::<_, Revision>(
+        "SELECT id, scraped_at, replaced_at FROM item_revisions WHERE item_id = $1 ORDER BY id DESC",
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await?;
+    Ok(Json(revisions))
+}
+
+/// Unified diff between a revision and the item's current content. The
+/// revision must be of the same URL; one of another item is a 400.
+#[instrument(skip(state))]
+pub async fn diff_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Query(query): Query<DiffQuery>,
+) -> Result<Json<DiffResponse>, AppError> {
+    let (url, content) = current_content(&state.db, id).await?;
+    let (revision_url, old_content): (String, String) =
+        sqlx::query_as("SELECT url, content FROM item_revisions WHERE id = $1")
+            .bind(query.against)
+            .fetch_optional(&state.db)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Revision {} not found", query.against)))?;
+    if revision_url != url {
+        return Err(AppError::BadRequest(format!(
+            "Revision {} is of {}, not {}",
+            query.against, revision_url, url
+        )));
+    }
+
+    let text_diff = TextDiff::from_lines(&old_content, &content);
+    let (mut added, mut removed) = (0, 0);
+    for change in text_diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => added += 1,
+            ChangeTag::Delete => removed += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+    let diff = text_diff
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(&format!("revision {}", query.against), &format!("item {}", id))
+        .to_string();
+    info!("Diffed item {} against revision {}: +{} -{}", id, query.against, added, removed);
+
+    Ok(Json(DiffResponse {
+        id,
+        against: query.against,
+        url,
+        added,
+        removed,
+        diff,
+    }))
+}
+
+// URL and content of a live item; 404 when it doesn't exist or is in the trash
+async fn current_content(db: &DbPool, id: i64) -> Result<(String, String), AppError> {
+    sqlx::query_as("SELECT url, content FROM scraped_items WHERE id = $1 AND deleted_at IS NULL")
+        .bind(id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Item {} not found", id)))
+}
@@ -0,0 +1,93 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, instrument};
+
+use crate::{AppError, AppState, ScrapedItem, ITEM_COLUMNS};
+
+// Most results returned for one search
+const MAX_RESULTS: i64 = 50;
+// Words of context around the highlighted match in a snippet
+const SNIPPET_WORDS: i64 = 16;
+
+#[derive(Deserialize, Debug)]
+pub struct SearchQuery {
+    q: Option<String>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct SearchResult {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    item: ScrapedItem,
+    snippet: String, // Matching passage of the content, matches wrapped in <mark>
+}
+
+/// Full-text search over stored items' URLs and content, best matches first.
+/// Every word in `q` must appear; FTS query syntax is not interpreted.
+#[instrument(skip(state))]
+pub async fn search_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<Vec<SearchResult>>, AppError> {
+    let q = query.q.as_deref().map(str::trim).unwrap_or_default();
+    if q.is_empty() {
+        return Err(AppError::BadRequest("q must not be empty".to_string()));
+    }
+
+    // The SQLite FTS table also has url and content columns, so qualify the item's
+    let columns: Vec<String> = ITEM_COLUMNS.split(',').map(|column| format!("s.{}", column.trim())).collect();
+    let results = sqlx::query_as::<_, SearchResult>(&search_sql(&columns.join(", ")))
+        .bind(match_expression(q))
+        .bind(SNIPPET_WORDS)
+        .bind(MAX_RESULTS)
+        .fetch_all(&state.db)
+        .await?;
+    info!("Search for {:?} matched {} items", q, results.len());
+    Ok(Json(results))
+}
+
+// $1 is the match expression, $2 the snippet length in words and $3 the result limit
+#[cfg(not(feature = "postgres"))]
+fn search_sql(columns: &str) -> String {
+    format!(
+        "SELECT {}, snippet(scraped_items_fts, 1, '<mark>', '</mark>', '…', $2) AS snippet
+         FROM scraped_items_fts JOIN scraped_items s ON s.id = scraped_items_fts.rowid
+         WHERE scraped_items_fts MATCH $1 AND s.deleted_at IS NULL
+         ORDER BY rank
+         LIMIT $3",
+        columns
+    )
+}
+
+// Postgres has no FTS5; the document expression matches the GIN index in migrations_postgres
+#[cfg(feature = "postgres")]
+fn search_sql(columns: &str) -> String {
+    format!(
+        "SELECT {}, ts_headline('simple', s.content, query,
+                    'StartSel=<mark>, StopSel=</mark>, FragmentDelimiter=…, MaxFragments=1, MinWords=1, MaxWords=' || $2) AS snippet
+         FROM scraped_items s, plainto_tsquery('simple', $1) query
+         WHERE to_tsvector('simple', s.url || ' ' || s.content) @@ query AND s.deleted_at IS NULL
+         ORDER BY ts_rank(to_tsvector('simple', s.url || ' ' || s.content), query) DESC
+         LIMIT $3",
+        columns
+    )
+}
+
+// plainto_tsquery already requires every word and ignores query syntax
+#[cfg(feature = "postgres")]
+fn match_expression(q: &str) -> String {
+    q.to_string()
+}
+
+// Quotes each word as an FTS5 string, so the query is matched literally
+#[cfg(not(feature = "postgres"))]
+fn match_expression(q: &str) -> String {
+    q.split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,68 @@
+use axum::{extract::State, Json};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{info, instrument};
+
+use crate::{AppError, AppState};
+
+// Hosts listed in the overview, most recently scraped first
+const TOP_HOSTS: i64 = 10;
+
+#[derive(Serialize)]
+pub struct StatsResponse {
+    total_items: i64,
+    total_content_bytes: i64, // UTF-8 size of all stored Markdown
+    earliest_created_at: Option<String>, // None while nothing is stored
+    latest_created_at: Option<String>,
+    recent_hosts: Vec<HostStats>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct HostStats {
+    host: String,
+    items: i64,
+    last_scraped_at: String,
+}
+
+/// Aggregate numbers about the stored corpus, for an overview screen. Items
+/// in the trash are left out.
+#[instrument(skip(state))]
+pub async fn stats_handler(State(state): State<Arc<AppState>>) -> Result<Json<StatsResponse>, AppError> {
+    let (total_items, total_content_bytes, earliest_created_at, latest_created_at): (
+        i64,
+        i64,
+        Option<String>,
+        Option<String>,
+    ) = sqlx::query_as(
+        "SELECT COUNT(*), COALESCE(SUM(octet_length(content)), 0), MIN(created_at), MAX(created_at)
+         FROM scraped_items WHERE deleted_at IS NULL",
+    )
+    .fetch_one(&state.db)
+    .await?;
+
+    // The host is what sits between "://" and the next '/'
+    let recent_hosts = sqlx::query_as::<_, HostStats>(
+        "WITH rests AS (
+             SELECT SUBSTR(url, INSTR(url, '://') + 3) AS rest, created_at
+             FROM scraped_items WHERE deleted_at IS NULL
+         ), hosts AS (
+             SELECT LOWER(CASE WHEN INSTR(rest, '/') > 0 THEN SUBSTR(rest, 1, INSTR(rest, '/') - 1) ELSE rest END) AS host,
+                    created_at
+             FROM rests
+         )
+         SELECT host, COUNT(*) AS items, MAX(created_at) AS last_scraped_at
+         FROM hosts GROUP BY host ORDER BY last_scraped_at DESC, host LIMIT $1",
+    )
+    .bind(TOP_HOSTS)
+    .fetch_all(&state.db)
+    .await?;
+
+    info!("Corpus stats: {} items, {} bytes", total_items, total_content_bytes);
+    Ok(Json(StatsResponse {
+        total_items,
+        total_content_bytes,
+        earliest_created_at,
+        latest_created_at,
+        recent_hosts,
+    }))
+}
@@ -0,0 +1,200 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::QueryBuilder;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tracing::{info, instrument};
+
+use crate::{
+    db::{Db, DbPool},
+    AppError, AppState, ScrapedItem,
+};
+
+// Bounds the size of a single bulk-tag transaction
+const MAX_BULK_IDS: usize = 1000;
+const MAX_TAG_LEN: usize = 50;
+// Ids bound into one `IN (...)` list, well under SQLite's and Postgres' bind limits
+const IDS_PER_QUERY: usize = 1000;
+
+#[derive(Deserialize, Debug)]
+pub struct BulkTagRequest {
+    ids: Vec<i64>,
+    #[serde(default)]
+    add: Vec<String>,
+    #[serde(default)]
+    remove: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct BulkTagResponse {
+    updated: u64, // Items that exist (and aren't in the trash) among the requested ids
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AddTagRequest {
+    tag: String,
+}
+
+#[derive(Serialize)]
+pub struct ItemTags {
+    id: i64,
+    tags: Vec<String>, // All of the item's tags after the change, sorted
+}
+
+/// Adds one tag to an item. Adding a tag it already has is a no-op.
+#[instrument(skip(state))]
+pub async fn add_tag_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(payload): Json<AddTagRequest>,
+) -> Result<Json<ItemTags>, AppError> {
+    let tag = normalize_tag(&payload.tag)?;
+    ensure_item_exists(&state.db, id).await?;
+    sqlx::query("INSERT INTO item_tags (item_id, tag) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+        .bind(id)
+        .bind(&tag)
+        .execute(&state.db)
+        .await?;
+    info!("Tagged item {} with {:?}", id, tag);
+    Ok(Json(ItemTags { id, tags: item_tags(&state.db, id).await? }))
+}
+
+/// Removes one tag from an item; 404 when the item doesn't have it.
+#[instrument(skip(state))]
+pub async fn remove_tag_handler(
+    State(state): State<Arc<AppState>>,
+    Path((id, tag)): Path<(i64, String)>,
+) -> Result<Json<ItemTags>, AppError> {
+    let tag = normalize_tag(&tag)?;
+    ensure_item_exists(&state.db, id).await?;
+    let removed = sqlx::query("DELETE FROM item_tags WHERE item_id = $1 AND tag = $2")
+        .bind(id)
+        .bind(&tag)
+        .execute(&state.db)
+        .await?
+        .rows_affected();
+    if removed == 0 {
+        return Err(AppError::NotFound(format!("Item {} has no tag {:?}", id, tag)));
+    }
+    info!("Removed tag {:?} from item {}", tag, id);
+    Ok(Json(ItemTags { id, tags: item_tags(&state.db, id).await? }))
+}
+
+/// Fills in the tags of `items`, with one query per `IDS_PER_QUERY` of them.
+pub async fn attach_tags(db: &DbPool, items: &mut [ScrapedItem]) -> Result<(), AppError> {
+    let ids: Vec<i64> = items.iter().map(|item| item.id).collect();
+    let mut rows: Vec<(i64, String)> = Vec::new();
+    for chunk in ids.chunks(IDS_PER_QUERY) {
+        let mut query = QueryBuilder::<Db>::new("SELECT item_id, tag FROM item_tags WHERE item_id IN (");
+        let mut ids = query.separated(", ");
+        for id in chunk {
+            ids.push_bind(*id);
+        }
+        query.push(") ORDER BY tag");
+        rows.extend(query.build_query_as::<(i64, String)>().fetch_all(db).await?);
+    }
+    let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+    for (item_id, tag) in rows {
+        tags.entry(item_id).or_default().push(tag);
+    }
+    for item in items {
+        item.tags = Some(tags.remove(&item.id).unwrap_or_default());
+    }
+    Ok(())
+}
+
+async fn ensure_item_exists(db: &DbPool, id: i64) -> Result<(), AppError> {
+    let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM scraped_items WHERE id = $1 AND deleted_at IS NULL")
+        .bind(id)
+        .fetch_optional(db)
+        .await?;
+    exists
+        .map(|_| ())
+        .ok_or_else(|| AppError::NotFound(format!("Item {} not found", id)))
+}
+
+async fn item_tags(db: &DbPool, id: i64) -> Result<Vec<String>, AppError> {
+    let tags = sqlx::query_scalar("SELECT tag FROM item_tags WHERE item_id = $1 ORDER BY tag")
+        .bind(id)
+        .fetch_all(db)
+        .await?;
+    Ok(tags)
+}
+
+/// Adds and removes tags across many items in one transaction. Tags are
+/// trimmed and lowercased; ids that don't exist or are in the trash are skipped.
+#[instrument(skip(state))]
+pub async fn bulk_tag_handler(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<BulkTagRequest>,
+) -> Result<Json<BulkTagResponse>, AppError> {
+    let ids: BTreeSet<i64> = payload.ids.iter().copied().collect();
+    if ids.is_empty() {
+        return Err(AppError::BadRequest("ids must not be empty".to_string()));
+    }
+    if ids.len() > MAX_BULK_IDS {
+        return Err(AppError::BadRequest(format!("At most {} ids can be tagged at once", MAX_BULK_IDS)));
+    }
+    let add = normalize_tags(&payload.add)?;
+    let remove = normalize_tags(&payload.remove)?;
+    if let Some(tag) = add.intersection(&remove).next() {
+        return Err(AppError::BadRequest(format!("Tag {:?} is both added and removed", tag)));
+    }
+
+    let mut tx = state.db.begin().await?;
+    let mut updated = 0u64;
+    for &id in &ids {
+        let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM scraped_items WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if exists.is_none() {
+            continue;
+        }
+        for tag in &add {
+            sqlx::query("INSERT INTO item_tags (item_id, tag) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+                .bind(id)
+                .bind(tag)
+                .execute(&mut *tx)
+                .await?;
+        }
+        for tag in &remove {
+            sqlx::query("DELETE FROM item_tags WHERE item_id = $1 AND tag = $2")
+                .bind(id)
+                .bind(tag)
+                .execute(&mut *tx)
+                .await?;
+        }
+        updated += 1;
+    }
+    tx.commit().await?;
+
+    info!(
+        "Tagged {} of {} items (added {:?}, removed {:?})",
+        updated,
+        ids.len(),
+        add,
+        remove
+    );
+    Ok(Json(BulkTagResponse { updated }))
+}
+
+fn normalize_tags(tags: &[String]) -> Result<BTreeSet<String>, AppError> {
+    tags.iter().map(|tag| normalize_tag(tag)).collect()
+}
+
+// Trimmed and lowercased; empty, whitespace-only and overlong tags are rejected
+fn normalize_tag(tag: &str) -> Result<String, AppError> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() || tag.chars().count() > MAX_TAG_LEN {
+        Err(AppError::BadRequest(format!(
+            "Tags must be 1 to {} characters long (got {:?})",
+            MAX_TAG_LEN, tag
+        )))
+    } else {
+        Ok(tag)
+    }
+}
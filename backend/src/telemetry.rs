@@ -0,0 +1,60 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use metrics::{describe_counter, describe_histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::sync::Arc;
+
+use crate::AppState;
+
+pub const SCRAPES_TOTAL: &str = "scrapes_total";
+pub const SCRAPE_CACHE_HITS_TOTAL: &str = "scrape_cache_hits_total";
+pub const SCRAPE_DURATION_SECONDS: &str = "scrape_duration_seconds";
+pub const ERRORS_TOTAL: &str = "errors_total";
+
+// Firecrawl scrapes take anywhere from sub-second to a minute
+const SCRAPE_DURATION_BUCKETS: &[f64] = &[0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0];
+
+/// Installs the global Prometheus recorder and describes the exported metrics.
+pub fn install_recorder() -> PrometheusHandle {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full(SCRAPE_DURATION_SECONDS.to_string()),
+            SCRAPE_DURATION_BUCKETS,
+        )
+        .expect("Scrape duration buckets must not be empty")
+        .install_recorder()
+        .expect("Failed to install Prometheus recorder");
+
+    describe_counter!(SCRAPES_TOTAL, "Scrape requests received");
+    describe_counter!(SCRAPE_CACHE_HITS_TOTAL, "Scrape requests served from the database cache");
+    describe_histogram!(SCRAPE_DURATION_SECONDS, "Duration of Firecrawl scrape calls in seconds");
+    describe_counter!(ERRORS_TOTAL, "Error responses returned, labelled by error type");
+
+    handle
+}
+
+/// `GET /metrics` in Prometheus text format.
+///
+/// Unauthenticated unless `METRICS_TOKEN` is set, in which case the request
+/// must carry `Authorization: Bearer <METRICS_TOKEN>`.
+pub async fn metrics_handler(State(state): State<Arc<AppState>>, headers: HeaderMap) -> Response {
+    if let Some(token) = &state.metrics_token {
+        let expected = format!("Bearer {}", token);
+        let authorized = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value == expected);
+        if !authorized {
+            return (StatusCode::UNAUTHORIZED, "Invalid or missing metrics token").into_response();
+        }
+    }
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics_handle.render(),
+    )
+        .into_response()
+}
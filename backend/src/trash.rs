@@ -0,0 +1,129 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, instrument};
+
+use crate::{db::DbPool, AppError, AppState, ScrapedItem, ITEM_COLUMNS};
+
+// How often expired trash is purged
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Moves an item to the trash. It disappears from history but can be restored
+/// until the purge task removes it for good.
+#[instrument(skip(state))]
+pub async fn delete_item_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    trash_item(&state.db, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// 404s when the item doesn't exist or is already in the trash
+async fn trash_item(db: &DbPool, id: i64) -> Result<(), AppError> {
+    let result = sqlx::query("UPDATE scraped_items SET deleted_at = datetime('now') WHERE id = $1 AND deleted_at IS NULL")
+        .bind(id)
+        .execute(db)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Item {} not found", id)));
+    }
+    info!("Moved item {} to the trash", id);
+    Ok(())
+}
+
+/// Lists trashed items, most recently deleted first.
+#[instrument(skip(state))]
+pub async fn list_trash_handler(State(state): State<Arc<AppState>>) -> Result<Json<Vec<ScrapedItem>>, AppError> {
+    let items = sqlx::query_as::<_, ScrapedItem>(&format!(
+        "SELECT {} FROM scraped_items WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+        ITEM_COLUMNS
+    ))
+    .fetch_all(&state.db)
+    .await?;
+    info!("Found {} items in the trash", items.len());
+    Ok(Json(items))
+}
+
+/// Takes an item back out of the trash and returns it. 404s when the item
+/// doesn't exist or isn't in the trash.
+#[instrument(skip(state))]
+pub async fn restore_item_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<ScrapedItem>, AppError> {
+    let item = sqlx::query_as::<_, ScrapedItem>(&format!(
+        "UPDATE scraped_items SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL RETURNING {}",
+        ITEM_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Item {} is not in the trash", id)))?;
+    info!("Restored item {} from the trash", id);
+    Ok(Json(item))
+}
+
+/// Hard-deletes items that have been in the trash longer than `retention_days`, hourly.
+pub fn spawn_purge_task(db: DbPool, retention_days: u32) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let result = sqlx::query(
+                "DELETE FROM scraped_items WHERE deleted_at IS NOT NULL AND deleted_at < datetime('now', $1)",
+            )
+            .bind(format!("-{} days", retention_days))
+            .execute(&db)
+            .await;
+            match result {
+                Ok(done) if done.rows_affected() > 0 => {
+                    info!("Purged {} items from the trash", done.rows_affected())
+                }
+                Ok(_) => {}
+                Err(e) => error!("Failed to purge the trash: {}", e),
+            }
+        }
+    });
+}
+
+// The tests use an in-memory SQLite database
+#[cfg(all(test, not(feature = "postgres")))]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn test_db() -> DbPool {
+        let db = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE scraped_items (id INTEGER PRIMARY KEY, deleted_at TEXT)")
+            .execute(&db)
+            .await
+            .unwrap();
+        db
+    }
+
+    #[tokio::test]
+    async fn deleting_a_nonexistent_item_is_not_found() {
+        let db = test_db().await;
+        let error = trash_item(&db, 42).await.unwrap_err();
+        assert!(matches!(error, AppError::NotFound(_)));
+        assert_eq!(error.into_response().status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn deleting_an_item_twice_is_not_found() {
+        let db = test_db().await;
+        sqlx::query("INSERT INTO scraped_items (id) VALUES (1)").execute(&db).await.unwrap();
+        trash_item(&db, 1).await.unwrap();
+        assert!(matches!(trash_item(&db, 1).await, Err(AppError::NotFound(_))));
+    }
+}
@@ -1,45 +1,253 @@
 use poll_promise::Promise;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::fmt;
 use serde_json;
 use egui_commonmark::CommonMarkViewer;
 use egui::ComboBox;
 
+use crate::diff_view;
+use crate::export::{self, ExportStyle};
+use crate::tables;
+use crate::processing::{self, LocalCache, ProcessingError};
+use crate::sanitize::{self, ViewerFilter};
+
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::JsCast;
 #[cfg(target_arch = "wasm32")]
 use web_sys::{HtmlElement, HtmlAnchorElement};
 
-#[cfg(not(target_arch = "wasm32"))]
-use printpdf::{Mm, PdfDocument}; // Removed Point
+use crate::pdf;
 
-// Define the backend URLs
-const FIRECROWL_URL: &str = "http://127.0.0.1:8000"; // Updated Port for Firecrowl (@backend)
-const LLM_SCRAPER_URL: &str = "http://127.0.0.1:3000"; // URL for LLM Scraper (@rust-web-scrapper)
+// Default backend URLs; overridable at build time with the FIRECROWL_URL and
+// LLM_SCRAPER_URL env vars, and at runtime in the top bar's settings
+const DEFAULT_FIRECROWL_URL: &str = "http://127.0.0.1:8000"; // Firecrowl (@backend)
+const DEFAULT_LLM_SCRAPER_URL: &str = "http://127.0.0.1:3000"; // LLM Scraper (@rust-web-scrapper)
+const HOVER_PREVIEW_CHARS: usize = 300; // Characters of Markdown shown when hovering a history row
+const UNDO_TOAST_SECS: f64 = 6.0; // How long a deletion can be undone
+const COPIED_LABEL_SECS: f64 = 1.5; // How long "Copied!" shows after copying the result
+const DEFAULT_EXPORT_WRAP_WIDTH: usize = 80;
+const JOB_POLL_INITIAL_SECS: f64 = 1.0; // First status check after a background job is queued
+const JOB_POLL_MAX_SECS: f64 = 10.0; // Backoff ceiling between status checks
+const DEFAULT_MAX_DISPLAY_BYTES: usize = 1024 * 1024; // Larger results are truncated on screen; laying out more can exhaust wasm memory
+// Offered in the empty content area
+const EXAMPLE_URLS: &[&str] = &["https://example.com", "https://www.rust-lang.org", "https://en.wikipedia.org/wiki/Web_scraping"];
+const DEFAULT_EXPORT_CONCURRENCY: usize = 4; // Items fetched at once by "Export all"
+const DEFAULT_CONTENT_WIDTH: f32 = 720.0; // Points, when limiting the content width is first turned on
+// Ctrl (Cmd on macOS) combos; plain keys are left to whichever widget has focus
+const SCRAPE_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Enter);
+const NEW_SCRAPE_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::N);
+const EXPORT_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::S);
 
 // Enum to represent the scraper type
 #[derive(Debug, PartialEq, Copy, Clone, serde::Deserialize, serde::Serialize)]
 enum ScraperType {
     Firecrowl, // Renamed from Backend
     LLM,       // Renamed from RustWebScraper
+    Local,     // In-process scraper (processing::fetch_and_convert), no backend needed
 }
 
+// Browsers only let the page read cross-origin responses the site allows (CORS)
+const LOCAL_SCRAPER_HINT: &str = if cfg!(target_arch = "wasm32") {
+    "Scrapes in the browser without a backend. Most sites don't allow cross-origin requests, so this mostly works in the native app."
+} else {
+    "Fetches and converts the page in this app, without a backend"
+};
+
 // Implement Display for ScraperType for the ComboBox
 impl fmt::Display for ScraperType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ScraperType::Firecrowl => write!(f, "Firecrowl"), // Updated display name
             ScraperType::LLM => write!(f, "LLM"),             // Updated display name
+            ScraperType::Local => write!(f, "Local"),
+        }
+    }
+}
+
+// How history rows are labelled. Title formats fall back to the host when a
+// result has no heading to use as its title.
+#[derive(Debug, PartialEq, Copy, Clone, serde::Deserialize, serde::Serialize)]
+enum HistoryLabel {
+    IndexHost,
+    Title,
+    FullUrl,
+    TitleHost,
+}
+
+impl fmt::Display for HistoryLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryLabel::IndexHost => write!(f, "Number and host"),
+            HistoryLabel::Title => write!(f, "Title"),
+            HistoryLabel::FullUrl => write!(f, "Full URL"),
+            HistoryLabel::TitleHost => write!(f, "Title and host"),
+        }
+    }
+}
+
+impl HistoryLabel {
+    const ALL: [HistoryLabel; 4] = [HistoryLabel::IndexHost, HistoryLabel::Title, HistoryLabel::FullUrl, HistoryLabel::TitleHost];
+
+    fn label(self, index: usize, item: &HistoryItem) -> String {
+        let host = url_host(&item.url);
+        match (self, item.title()) {
+            (HistoryLabel::IndexHost, _) => format!("{}: {}", index + 1, host),
+            (HistoryLabel::FullUrl, _) => item.url.clone(),
+            (HistoryLabel::Title, Some(title)) => title.to_string(),
+            (HistoryLabel::TitleHost, Some(title)) => format!("{} ({})", title, host),
+            (HistoryLabel::Title | HistoryLabel::TitleHost, None) => host.to_string(),
+        }
+    }
+}
+
+// Client-side timeout for each scraper, in seconds. The LLM path summarizes
+// after fetching, so it gets much longer than the others by default.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+struct ScrapeTimeouts {
+    firecrowl_secs: u64,
+    llm_secs: u64,
+    local_secs: u64,
+}
+
+impl Default for ScrapeTimeouts {
+    fn default() -> Self {
+        Self {
+            firecrowl_secs: 60,
+            llm_secs: 180,
+            local_secs: 30,
+        }
+    }
+}
+
+impl ScrapeTimeouts {
+    fn get_mut(&mut self, scraper: ScraperType) -> &mut u64 {
+        match scraper {
+            ScraperType::Firecrowl => &mut self.firecrowl_secs,
+            ScraperType::LLM => &mut self.llm_secs,
+            ScraperType::Local => &mut self.local_secs,
+        }
+    }
+
+    fn get(&self, scraper: ScraperType) -> u64 {
+        match scraper {
+            ScraperType::Firecrowl => self.firecrowl_secs,
+            ScraperType::LLM => self.llm_secs,
+            ScraperType::Local => self.local_secs,
         }
     }
 }
 
+// Representations requested from Firecrowl besides Markdown, which is always
+// returned. Sent as the `formats` of POST /scrape.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+struct ScrapeFormats {
+    html: bool,
+    links: bool,
+}
+
+impl ScrapeFormats {
+    fn names(&self) -> Vec<&'static str> {
+        let mut names = Vec::new();
+        if self.html {
+            names.push("html");
+        }
+        if self.links {
+            names.push("links");
+        }
+        names
+    }
+
+    // Short label for the picker, e.g. "MD+HTML"
+    fn label(&self) -> String {
+        let mut label = "MD".to_string();
+        if self.html {
+            label.push_str("+HTML");
+        }
+        if self.links {
+            label.push_str("+Links");
+        }
+        label
+    }
+}
+
 // Define structs matching Backend API Responses
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct FirecrowlScrapeResponse {
     id: i64,
     url: String,
     content: String, // Markdown content from backend
+    #[serde(default)]
+    mobile_content: Option<String>, // Mobile-viewport Markdown, when requested
+    #[serde(default)]
+    images_stripped: bool, // Backend removed image references before storing
+    #[serde(default)]
+    readability: Option<bool>, // Set when readability was requested: whether `content` is the article
+    #[serde(default)]
+    title: Option<String>, // The page's <title>, when it has one
+    #[serde(default)]
+    html: Option<String>, // Cleaned page HTML, when requested in `formats`
+    #[serde(default)]
+    links: Option<Vec<String>>, // URLs linked from the page, when requested in `formats`
+}
+
+// Where the two scraping backends are reached; persisted with the app
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct BackendUrls {
+    firecrowl: String,
+    llm_scraper: String,
+}
+
+impl Default for BackendUrls {
+    fn default() -> Self {
+        Self {
+            firecrowl: option_env!("FIRECROWL_URL").unwrap_or(DEFAULT_FIRECROWL_URL).to_string(),
+            llm_scraper: option_env!("LLM_SCRAPER_URL").unwrap_or(DEFAULT_LLM_SCRAPER_URL).to_string(),
+        }
+    }
+}
+
+impl BackendUrls {
+    /// The defaults with `127.0.0.1` swapped for `host`, for a web build served
+    /// from the machine running the backends. Build-time URLs are kept as set.
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    fn on_host(host: &str) -> Self {
+        let with_host = |url: &str| {
+            let Ok(mut parsed) = reqwest::Url::parse(url) else { return url.to_string() };
+            if host.is_empty() || parsed.set_host(Some(host)).is_err() {
+                return url.to_string();
+            }
+            parsed.as_str().trim_end_matches('/').to_string()
+        };
+        let mut urls = Self::default();
+        if option_env!("FIRECROWL_URL").is_none() {
+            urls.firecrowl = with_host(&urls.firecrowl);
+        }
+        if option_env!("LLM_SCRAPER_URL").is_none() {
+            urls.llm_scraper = with_host(&urls.llm_scraper);
+        }
+        urls
+    }
+
+    /// Checks both URLs, returning them normalized (trimmed, without a
+    /// trailing slash) or a message naming the first invalid one.
+    fn validated(&self) -> Result<Self, String> {
+        Ok(Self {
+            firecrowl: validate_backend_url("Firecrowl", &self.firecrowl)?,
+            llm_scraper: validate_backend_url("LLM Scraper", &self.llm_scraper)?,
+        })
+    }
+}
+
+fn validate_backend_url(name: &str, url: &str) -> Result<String, String> {
+    let url = url.trim().trim_end_matches('/');
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.host().is_some() => Ok(url.to_string()),
+        Ok(_) => Err(format!("{} URL must be an http:// or https:// URL with a host", name)),
+        Err(e) => Err(format!("{} URL is invalid: {}", name, e)),
+    }
 }
 
 // Define struct matching LLM Scraper API Response
@@ -48,9 +256,12 @@ struct LlmScrapeResponse {
     url: String,
     #[serde(rename = "summary_markdown")] // Match backend field name
     summary: String,
+    // Optional metadata; older LLM backends leave these out
+    #[serde(default)]
+    scraped_at: Option<String>, // RFC 3339 timestamp of the scrape
+    #[serde(default)]
+    word_count: Option<usize>, // Words on the scraped page
     // Add other fields if needed, matching the backend's models.rs ScrapeResponse
-    // scraped_at: Option<String>, // Using Option<String> for simplicity if DateTime parsing is complex
-    // word_count: Option<usize>,
     // status: Option<String>,
 }
 
@@ -69,24 +280,305 @@ struct LlmResponseMeta {
     message: Option<String>,
 }
 
+// A background scrape job (GET /jobs/:id). Submitting one returns the same
+// shape with `job_id` instead of `id`.
+#[derive(Deserialize, Debug, Clone)]
+struct JobStatus {
+    #[serde(alias = "job_id")]
+    id: i64,
+    status: String, // pending, running, done or failed
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    result: Option<FirecrowlScrapeResponse>, // Set once done
+}
+
+// A Firecrowl scrape running as a backend job, polled with backoff
+struct ScrapeJob {
+    job_id: Option<i64>, // None until the submission returns
+    status: String,
+    request: Option<Promise<Result<JobStatus, FrontendError>>>, // Submission or status check in flight
+    next_poll_at: f64, // `ctx.input(|i| i.time)` of the next status check
+    poll_interval: f64,
+}
+
+impl ScrapeJob {
+    // Label for the loading state
+    fn status_text(&self) -> String {
+        match (self.job_id, self.status.as_str()) {
+            (None, _) => "Submitting job...".to_string(),
+            (Some(id), "running") => format!("Processing (job #{})...", id),
+            (Some(id), "done") => format!("Done (job #{})", id),
+            (Some(id), _) => format!("Queued (job #{})...", id),
+        }
+    }
+}
+
+// An item as stored by the backend (GET /history/:id, GET /trash), matching its ScrapedItem
+#[derive(Deserialize, Debug, Clone)]
+struct StoredItem {
+    id: i64,
+    url: String,
+    content: String,
+    #[serde(default)]
+    images_stripped: bool,
+    #[serde(default)]
+    deleted_at: Option<String>,
+    #[serde(default)]
+    title: Option<String>,
+}
+
+// An "Export all" in progress. Items stored by the backend are re-fetched so
+// the export has their current content, a few at a time; the rest (and any
+// that fail to fetch) use the copy in history. The history is copied when the
+// export starts, so deleting or restoring items meanwhile doesn't affect it.
+struct ExportAll {
+    items: Vec<ExportEntry>,
+    pending: VecDeque<usize>, // Positions in `items` not started yet
+    in_flight: Vec<(usize, Promise<Result<StoredItem, FrontendError>>)>,
+    contents: Vec<Option<String>>, // Per position in `items`, once ready
+    failures: Vec<String>,
+}
+
+// A history item as it was when "Export all" started
+struct ExportEntry {
+    url: String,
+    id: Option<i64>,
+    markdown: String,
+}
+
+impl ExportAll {
+    fn progress(&self) -> (usize, usize) {
+        let done = self.contents.iter().filter(|content| content.is_some()).count();
+        (done, self.contents.len())
+    }
+}
+
+// A history item removed in this session, kept until its Undo toast expires
+struct DeletedHistoryItem {
+    index: usize, // Position it was removed from
+    item: HistoryItem,
+    deleted_at: f64, // `ctx.input(|i| i.time)` at deletion
+}
+
 // Enum to hold the active promise, distinguishing its type
 enum ActivePromise {
-    Firecrowl(Promise<Result<FirecrowlScrapeResponse, FrontendError>>),
+    Firecrowl(String, Promise<Result<FirecrowlScrapeResponse, FrontendError>>), // Requested URL + response
     Llm(Promise<Result<LlmApiResponse<LlmScrapeResponse>, FrontendError>>),
+    Local(String, Promise<Result<String, ProcessingError>>), // Requested URL + Markdown
+    Job(ScrapeJob), // Firecrowl scrape queued with `async`
 }
 
-// Result type for the promise, holding either response type
+// A successful scrape from any scraper
 #[derive(Debug, Clone)]
 enum ScrapeResult {
     Firecrowl(FirecrowlScrapeResponse),
     Llm(LlmScrapeResponse),
+    Local { url: String, markdown: String },
+}
+
+impl ScrapeResult {
+    /// The unified shape the UI shows and keeps in history: URL, Markdown,
+    /// and whatever metadata the scraper provides.
+    fn into_history_item(self) -> HistoryItem {
+        match self {
+            ScrapeResult::Firecrowl(response) => HistoryItem {
+                versions: ContentVersions::from_response(&response),
+                id: Some(response.id),
+                url: response.url,
+                page_title: response.title,
+                markdown: response.content,
+                mobile_markdown: response.mobile_content,
+                images_stripped: response.images_stripped,
+                fallback_reason: None,
+                scraped_at: None,
+                word_count: None,
+                html: response.html,
+                links: response.links,
+            },
+            ScrapeResult::Llm(response) => HistoryItem {
+                id: None,
+                url: response.url,
+                page_title: None,
+                markdown: response.summary,
+                versions: ContentVersions::default(),
+                mobile_markdown: None,
+                images_stripped: false,
+                fallback_reason: None,
+                scraped_at: response.scraped_at,
+                word_count: response.word_count,
+                html: None,
+                links: None,
+            },
+            ScrapeResult::Local { url, markdown } => HistoryItem {
+                id: None,
+                url,
+                page_title: None,
+                markdown,
+                versions: ContentVersions::default(),
+                mobile_markdown: None,
+                images_stripped: false,
+                fallback_reason: None,
+                scraped_at: None,
+                word_count: None,
+                html: None,
+                links: None,
+            },
+        }
+    }
+}
+
+impl ActivePromise {
+    /// The scraper and outcome of a finished request, or None while it's still
+    /// running. Background jobs are driven by `TemplateApp::poll_scrape_job` instead.
+    fn finished_outcome(&self) -> Option<(ScraperType, Result<ScrapeResult, String>)> {
+        match self {
+            ActivePromise::Firecrowl(_, promise) => {
+                let result = promise.ready()?.as_ref().map_err(|e| e.to_string());
+                Some((ScraperType::Firecrowl, result.map(|response| ScrapeResult::Firecrowl(response.clone()))))
+            }
+            ActivePromise::Llm(promise) => {
+                let result = match promise.ready()? {
+                    Ok(api_response) => api_response.data.clone().map(ScrapeResult::Llm).ok_or_else(|| {
+                        log::error!("LLM API Response successful but data field is None. Meta: {:?}", api_response.meta);
+                        "API returned success but no data".to_string()
+                    }),
+                    Err(e) => Err(e.to_string()),
+                };
+                Some((ScraperType::LLM, result))
+            }
+            ActivePromise::Local(url, promise) => {
+                let result = promise.ready()?.as_ref().map_err(|e| e.to_string());
+                let result = result.map(|markdown| ScrapeResult::Local { url: url.clone(), markdown: markdown.clone() });
+                Some((ScraperType::Local, result))
+            }
+            ActivePromise::Job(_) => None,
+        }
+    }
 }
 
 // Simplified representation for history
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct HistoryItem {
+    #[serde(default)]
+    id: Option<i64>, // Backend item id, for Firecrowl results
     url: String,
+    #[serde(default)]
+    page_title: Option<String>, // Title reported by the backend, for Firecrowl results
     markdown: String,
+    #[serde(default)]
+    mobile_markdown: Option<String>,
+    #[serde(default)]
+    images_stripped: bool,
+    #[serde(default)]
+    fallback_reason: Option<String>, // Firecrowl error, when this is a Local scrape run in its place
+    #[serde(default)]
+    versions: ContentVersions,
+    #[serde(default)]
+    scraped_at: Option<String>, // Reported by the LLM scraper
+    #[serde(default)]
+    word_count: Option<usize>, // Reported by the LLM scraper
+    #[serde(default)]
+    html: Option<String>, // Page HTML, for Firecrowl results scraped with it
+    #[serde(default)]
+    links: Option<Vec<String>>, // Linked URLs, for Firecrowl results scraped with them
+}
+
+/// A result as saved by the JSON export.
+#[derive(serde::Serialize)]
+struct ResultExport<'a> {
+    url: Option<&'a str>, // None for results not in history
+    title: Option<&'a str>,
+    scraped_at: Option<&'a str>,
+    word_count: usize,
+    content: &'a str,
+}
+
+/// The full Markdown and readability article of a Firecrowl result, as far
+/// as they've been fetched. Either can be missing until it's toggled to.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct ContentVersions {
+    full: Option<String>,
+    article: Option<String>,
+    no_article: bool, // The readability pass found no article; the full Markdown stands in
+}
+
+// A request for the version of a history item that hasn't been fetched yet
+struct VersionFetch {
+    item_id: i64,
+    readability: bool,
+    promise: Promise<Result<FirecrowlScrapeResponse, FrontendError>>,
+}
+
+impl ContentVersions {
+    fn from_response(response: &FirecrowlScrapeResponse) -> Self {
+        let mut versions = Self::default();
+        versions.record(response.content.clone(), response.readability);
+        versions
+    }
+
+    /// Stores a response's content under the version it turned out to be.
+    fn record(&mut self, content: String, readability: Option<bool>) {
+        match readability {
+            Some(true) => self.article = Some(content),
+            Some(false) => {
+                self.full = Some(content);
+                self.no_article = true;
+            }
+            None => self.full = Some(content),
+        }
+    }
+
+    /// The article or full Markdown, or None when it has yet to be fetched.
+    fn get(&self, readability: bool) -> Option<&String> {
+        if readability && !self.no_article {
+            self.article.as_ref()
+        } else {
+            self.full.as_ref()
+        }
+    }
+}
+
+impl HistoryItem {
+    /// Whether the item's URL, or with `in_content` its Markdown, contains
+    /// `needle`, ignoring case. `needle` must already be lowercase.
+    fn matches(&self, needle: &str, in_content: bool) -> bool {
+        self.url.to_lowercase().contains(needle) || (in_content && self.markdown.to_lowercase().contains(needle))
+    }
+
+    /// What the scraper reported about the result, e.g. "Scraped 2024-01-02 ·
+    /// 1,230 words", or None when it reported nothing.
+    fn metadata_line(&self) -> Option<String> {
+        let scraped = self.scraped_at.as_deref().map(|scraped_at| {
+            let date = chrono::DateTime::parse_from_rfc3339(scraped_at)
+                .map_or_else(|_| scraped_at.to_string(), |time| time.format("%Y-%m-%d").to_string());
+            format!("Scraped {}", date)
+        });
+        let words = self.word_count.map(|count| format!("{} words", format_count(count)));
+        let parts: Vec<String> = scraped.into_iter().chain(words).collect();
+        (!parts.is_empty()).then(|| parts.join(" · "))
+    }
+
+    /// The page title the backend reported, or else the result's first
+    /// Markdown heading near the top.
+    fn title(&self) -> Option<&str> {
+        if let Some(title) = &self.page_title {
+            return Some(title);
+        }
+        let mut in_fence = false;
+        for line in self.markdown.lines().take(40) {
+            let line = line.trim();
+            if line.starts_with("```") || line.starts_with("~~~") {
+                in_fence = !in_fence;
+            } else if !in_fence && line.starts_with('#') {
+                let title = line.trim_start_matches('#').trim().trim_end_matches('#').trim();
+                if !title.is_empty() {
+                    return Some(title);
+                }
+            }
+        }
+        None
+    }
 }
 
 // Custom Error type for Frontend operations
@@ -94,8 +586,10 @@ struct HistoryItem {
 enum FrontendError {
     Http(reqwest::Error), // Keep for now, although ehttp is primary now
     EHttp(String),        // Add variant for ehttp errors
-    JsonParse(serde_json::Error),
-    ApiError(String), // Errors reported by the backend API
+    JsonParse(serde_json::Error, Option<String>), // With the start of the body, when it was at hand
+    // Errors reported by the backend API; `code` is its machine-readable kind, when it sent one
+    ApiError { code: Option<String>, message: String },
+    UnexpectedResponse(String), // Body didn't look like what the service should return
     Other(String),
 }
 
@@ -104,8 +598,16 @@ impl fmt::Display for FrontendError {
         match self {
             FrontendError::Http(e) => write!(f, "HTTP request failed: {}", e),
             FrontendError::EHttp(e) => write!(f, "HTTP request failed: {}", e),
-            FrontendError::JsonParse(e) => write!(f, "Failed to parse JSON response: {}", e),
-            FrontendError::ApiError(msg) => write!(f, "API Error: {}", msg),
+            FrontendError::JsonParse(e, None) => write!(f, "Failed to parse JSON response: {}", e),
+            FrontendError::JsonParse(e, Some(snippet)) => {
+                write!(f, "Failed to parse JSON response: {}. Response starts with: \"{}\"", e, snippet)
+            }
+            FrontendError::ApiError { code, message } => match code.as_deref() {
+                Some("INVALID_API_KEY") => write!(f, "API Error: {} (set the API key in ⚙ Options)", message),
+                Some("RATE_LIMITED") | Some("TIMEOUT") => write!(f, "{}", message),
+                _ => write!(f, "API Error: {}", message),
+            },
+            FrontendError::UnexpectedResponse(msg) => write!(f, "{}", msg),
             FrontendError::Other(msg) => write!(f, "Error: {}", msg),
         }
     }
@@ -128,7 +630,7 @@ impl From<ehttp::Error> for FrontendError {
 // Convert serde_json errors
 impl From<serde_json::Error> for FrontendError {
     fn from(err: serde_json::Error) -> Self {
-        FrontendError::JsonParse(err)
+        FrontendError::JsonParse(err, None)
     }
 }
 
@@ -138,40 +640,818 @@ impl From<serde_json::Error> for FrontendError {
 pub struct TemplateApp {
     input_url: String,
     #[serde(skip)]
+    input_url_error: Option<String>, // Why the typed URL was rejected before scraping
+    #[serde(skip)]
+    focus_url_input: bool, // Move keyboard focus to the URL field on the next frame
+    #[serde(skip)]
     markdown_content: Option<String>,
     #[serde(skip)]
     error_message: Option<String>,
     #[serde(skip)]
     scrape_promise: Option<ActivePromise>,
-    #[serde(skip)]
-    scrape_history: Vec<HistoryItem>,
+    scrape_history: Vec<HistoryItem>, // Persisted, so history survives restarts
     #[serde(skip)]
     selected_history_index: Option<usize>,
     #[serde(skip)]
     is_displaying_result: bool,
     #[serde(skip)]
+    keep_input_row: bool, // Input row stays visible while a result is displayed
+    #[serde(skip)]
     selected_scraper: ScraperType,
+    scrape_mobile: bool, // Also request the mobile-viewport variant (Firecrowl only)
+    scrape_formats: ScrapeFormats, // Extra formats to request (Firecrowl only)
+    exclude_images: bool, // Ask the backend to strip images (Firecrowl only)
+    prefer_readability: bool, // Show the readability article instead of the full Markdown (Firecrowl only)
+    #[serde(skip)]
+    version_fetch: Option<VersionFetch>,
+    accept_language: String, // Accept-Language sent with Firecrowl scrapes; empty sends none
+    api_key: String, // Bearer key for the backend's scrape and write endpoints (its API_KEY); empty sends none
+    backend_urls: BackendUrls,
+    #[serde(skip)]
+    backend_urls_draft: Option<(BackendUrls, Option<String>)>, // Edits in the settings menu, with the validation error
+    local_cache: LocalCache, // Persisted results of the Local scraper
+    local_cache_ttl_mins: u64,
+    bypass_local_cache: bool, // Force a fresh Local fetch even when cached
+    llm_instructions: String, // Optional summarization style for the LLM scraper; empty uses the default
+    auto_scrape_on_paste: bool, // Start a scrape as soon as a complete URL is pasted into the input
+    show_start_hints: bool, // Example URLs and tips while nothing has been scraped
+    #[serde(skip)]
+    first_run: bool, // No saved state was found; show the welcome panel
+    async_scrape: bool, // Queue Firecrowl scrapes as backend jobs and poll for the result
+    local_fallback: bool, // Retry a failed Firecrowl scrape with the Local scraper
+    #[serde(skip)]
+    fallback_reason: Option<String>, // Firecrowl error while its Local fallback runs
+    scrape_timeouts: ScrapeTimeouts,
+    history_label: HistoryLabel, // How history rows are labelled
+    history_hides_input: bool, // Selecting a history item swaps the input row for "New"
+    export_concurrency: usize, // Items "Export all" fetches from the backend at once
+    #[serde(skip)]
+    export_all: Option<ExportAll>,
+    #[serde(skip)]
+    export_all_report: Option<String>, // Outcome of the last "Export all", until dismissed
+    export_wrap_width: Option<usize>, // Hard-wrap Markdown exports at this width; None keeps the source lines
+    content_font_scale: f32, // Text size of the scraped content, relative to the UI's body text
+    viewer_filter: ViewerFilter, // Elements the content viewer renders
+    content_width: Option<f32>, // Maximum width of the scraped content in points; None fills the panel
+    max_display_bytes: usize, // Results larger than this are truncated on screen (exports stay complete)
+    #[serde(skip)]
+    scrape_deadline: Option<(f64, ScraperType)>, // When the active scrape times out (`ctx.input(|i| i.time)`); None for background jobs
+    #[serde(skip)]
+    scrape_started_at: Option<f64>, // `ctx.input(|i| i.time)` when the active scrape started, for the elapsed timer
+    slow_scrape_hint_secs: u64, // After this long, the loading view says the scrape is taking longer than usual
+    #[serde(skip)]
+    show_mobile: bool, // Whether the mobile variant is the one being displayed
+    #[serde(skip)]
+    scrape_queue: VecDeque<(String, ScraperType)>, // Scrapes waiting for the active one to finish
+    #[serde(skip)]
+    compare_mode: bool, // History rows become checkboxes for picking two items to diff
+    #[serde(skip)]
+    compare_selection: Vec<usize>, // Up to two history indices, oldest pick first
+    #[serde(skip)]
+    history_filter: String, // Only history rows matching this are listed
+    history_filter_content: bool, // The filter also searches the Markdown, not just URLs
+    #[serde(skip)]
+    show_tables: bool, // Render the result's Markdown tables as sortable grids instead of the document
+    show_source: bool, // Show the literal Markdown instead of rendering it
+    #[serde(skip)]
+    last_deleted: Option<DeletedHistoryItem>, // Most recent deletion, while it can still be undone
+    #[serde(skip)]
+    copied_at: Option<f64>, // `ctx.input(|i| i.time)` of the last copy, for the "Copied!" label
+    #[serde(skip)]
+    show_trash: bool, // History panel lists the backend's trash instead of history
+    #[serde(skip)]
+    trash_items: Vec<StoredItem>,
+    #[serde(skip)]
+    trash_promise: Option<Promise<Result<Vec<StoredItem>, FrontendError>>>,
+    #[serde(skip)]
+    restore_promise: Option<Promise<Result<StoredItem, FrontendError>>>,
 }
 
 impl Default for TemplateApp {
     fn default() -> Self {
         Self {
             input_url: "".to_owned(),
+            input_url_error: None,
+            focus_url_input: false,
             markdown_content: None,
             error_message: None,
             scrape_promise: None,
             scrape_history: Vec::new(),
             selected_history_index: None,
             is_displaying_result: false,
+            keep_input_row: false,
             selected_scraper: ScraperType::Firecrowl, // Default to Firecrowl
+            scrape_mobile: false,
+            scrape_formats: ScrapeFormats::default(),
+            exclude_images: false,
+            prefer_readability: false,
+            version_fetch: None,
+            accept_language: String::new(),
+            api_key: String::new(),
+            backend_urls: BackendUrls::default(),
+            backend_urls_draft: None,
+            local_cache: LocalCache::default(),
+            local_cache_ttl_mins: 60,
+            bypass_local_cache: false,
+            llm_instructions: String::new(),
+            auto_scrape_on_paste: false,
+            show_start_hints: true,
+            first_run: false,
+            async_scrape: false,
+            local_fallback: false,
+            fallback_reason: None,
+            scrape_timeouts: ScrapeTimeouts::default(),
+            history_label: HistoryLabel::IndexHost,
+            history_hides_input: true,
+            export_concurrency: DEFAULT_EXPORT_CONCURRENCY,
+            export_all: None,
+            export_all_report: None,
+            export_wrap_width: None,
+            content_font_scale: 1.0,
+            viewer_filter: ViewerFilter::default(),
+            content_width: None,
+            max_display_bytes: DEFAULT_MAX_DISPLAY_BYTES,
+            scrape_deadline: None,
+            scrape_started_at: None,
+            slow_scrape_hint_secs: 20,
+            show_mobile: false,
+            scrape_queue: VecDeque::new(),
+            compare_mode: false,
+            compare_selection: Vec::new(),
+            history_filter: String::new(),
+            history_filter_content: false,
+            show_tables: false,
+            show_source: false,
+            last_deleted: None,
+            copied_at: None,
+            show_trash: false,
+            trash_items: Vec::new(),
+            trash_promise: None,
+            restore_promise: None,
+        }
+    }
+}
+
+impl TemplateApp {
+    /// Called once before the first frame.
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        // Nothing saved yet means this is the first time the app is opened
+        let first_run = cc.storage.map_or(true, |storage| storage.get_string(eframe::APP_KEY).is_none());
+        // Settings and history from the last session; unreadable state starts fresh
+        let saved: Option<Self> = cc.storage.and_then(|storage| eframe::get_value(storage, eframe::APP_KEY));
+        let Some(saved) = saved else {
+            // The web build expects the backends on the host it was served from
+            #[cfg(target_arch = "wasm32")]
+            let backend_urls = BackendUrls::on_host(&cc.integration_info.web_info.location.hostname);
+            #[cfg(not(target_arch = "wasm32"))]
+            let backend_urls = BackendUrls::default();
+            return Self { first_run, backend_urls, ..Default::default() };
+        };
+        Self { first_run, ..saved }
+    }
+
+    /// Spawns the scrape promise for `url` using the given scraper.
+    fn start_scrape(&mut self, ctx: &egui::Context, url: String, scraper: ScraperType) {
+        log::info!("Scrape triggered for URL: {}", url);
+        self.error_message = None;
+        self.markdown_content = None;
+        self.selected_history_index = None;
+
+        // --- Create and Spawn Promise ---
+        let active_promise_enum = match scraper {
+            ScraperType::Firecrowl => {
+                let request = self.firecrowl_request(&url);
+                log::info!("Requesting Firecrowl POST scrape to: {}", request.url);
+                if self.async_scrape {
+                    ActivePromise::Job(ScrapeJob {
+                        job_id: None,
+                        status: "pending".to_string(),
+                        request: Some(spawn_scrape_promise(ctx, request, self.max_display_bytes, parse_json_response::<JobStatus>)),
+                        next_poll_at: 0.0,
+                        poll_interval: JOB_POLL_INITIAL_SECS,
+                    })
+                } else {
+                    let promise = spawn_scrape_promise(ctx, request, self.max_display_bytes, parse_json_response::<FirecrowlScrapeResponse>);
+                    // Wrap in enum variant
+                    ActivePromise::Firecrowl(url, promise)
+                }
+            }
+            ScraperType::LLM => {
+                let request = self.llm_request(&url);
+                log::info!("Requesting LLM POST scrape to: {}", request.url);
+                let promise = spawn_scrape_promise(ctx, request, self.max_display_bytes, parse_llm_response);
+                // Wrap in enum variant
+                ActivePromise::Llm(promise)
+            }
+            ScraperType::Local => {
+                let ttl_secs = self.local_cache_ttl_mins * 60;
+                if !self.bypass_local_cache {
+                    if let Some(markdown) = self.local_cache.get(&url, ttl_secs) {
+                        log::info!("Local cache hit for URL: {}", url);
+                        let markdown = markdown.to_owned();
+                        self.show_result(ScrapeResult::Local { url, markdown });
+                        return;
+                    }
+                }
+                log::info!("Scraping locally: {}", url);
+                ActivePromise::Local(url.clone(), spawn_local_scrape(url))
+            }
+        };
+        // Background jobs are polled until the backend finishes them, so only direct requests time out
+        self.scrape_deadline = match active_promise_enum {
+            ActivePromise::Job(_) => None,
+            _ => Some((ctx.input(|i| i.time) + self.scrape_timeouts.get(scraper) as f64, scraper)),
+        };
+        self.scrape_started_at = Some(ctx.input(|i| i.time));
+        self.scrape_promise = Some(active_promise_enum);
+        // --- End Promise Creation ---
+    }
+
+    /// Abandons the active scrape and returns to the input. In-flight requests
+    /// can't be aborted (on native they run on their own thread), so one may
+    /// still complete in the background; its result is dropped with the promise.
+    /// A queued scrape starts next, as when a scrape finishes.
+    fn cancel_scrape(&mut self, ctx: &egui::Context) {
+        log::info!("Scrape cancelled");
+        self.scrape_promise = None;
+        self.scrape_deadline = None;
+        self.scrape_started_at = None;
+        self.fallback_reason = None;
+        self.is_displaying_result = false;
+        if let Some((url, scraper)) = self.scrape_queue.pop_front() {
+            self.start_scrape(ctx, url, scraper);
+        }
+    }
+
+    /// The backend request for a Firecrowl scrape of `url` with the current options.
+    fn firecrowl_request(&self, url: &str) -> ehttp::Request {
+        let mut request_body = serde_json::json!({
+            "url": url,
+            "mobile": self.scrape_mobile,
+            "exclude_images": self.exclude_images,
+        });
+        let accept_language = self.accept_language.trim();
+        if !accept_language.is_empty() {
+            request_body["accept_language"] = accept_language.into();
+        }
+        if self.prefer_readability {
+            request_body["readability"] = true.into();
+        }
+        if self.async_scrape {
+            request_body["async"] = true.into();
+        }
+        let formats = self.scrape_formats.names();
+        if !formats.is_empty() {
+            request_body["formats"] = formats.into();
+        }
+        self.scrape_request(&request_body)
+    }
+
+    /// POST /scrape with `body`, carrying the API key when one is set.
+    fn scrape_request(&self, body: &serde_json::Value) -> ehttp::Request {
+        let mut request = ehttp::Request::post(format!("{}/scrape", self.backend_urls.firecrowl), body.to_string().into_bytes());
+        request.headers = ehttp::Headers::new(&[("Content-Type", "application/json")]);
+        self.with_api_key(request)
+    }
+
+    /// Adds the API key, when one is set, for Firecrowl routes that require it.
+    fn with_api_key(&self, mut request: ehttp::Request) -> ehttp::Request {
+        let api_key = self.api_key.trim();
+        if !api_key.is_empty() {
+            request.headers.insert("Authorization", format!("Bearer {}", api_key));
+        }
+        request
+    }
+
+    /// The request for an LLM scrape of `url` with the current instructions.
+    fn llm_request(&self, url: &str) -> ehttp::Request {
+        let request_url = format!("{}/api/scrape", self.backend_urls.llm_scraper);
+        let mut request_body = serde_json::json!({ "url": url });
+        let instructions = self.llm_instructions.trim();
+        if !instructions.is_empty() {
+            request_body["instructions"] = instructions.into();
+        }
+        let mut request = ehttp::Request::post(request_url, request_body.to_string().into_bytes());
+        request.headers = ehttp::Headers::new(&[("Content-Type", "application/json")]);
+        request
+    }
+
+    /// Displays a scrape result and records it in history.
+    fn show_result(&mut self, result: ScrapeResult) {
+        let mut history_item = result.into_history_item();
+        history_item.fallback_reason = self.fallback_reason.take();
+        self.markdown_content = Some(history_item.markdown.clone());
+        self.show_mobile = false;
+        self.error_message = None;
+        self.is_displaying_result = true;
+        self.keep_input_row = false;
+        if self.scrape_history.last().map_or(true, |last| last.url != history_item.url) {
+            self.scrape_history.push(history_item);
+        }
+        self.selected_history_index = Some(self.scrape_history.len() - 1);
+    }
+
+    /// Drives an active background job: checks its status when due, backing
+    /// off between checks, and shows the result once it's done. Returns true
+    /// when the job has finished, successfully or not.
+    fn poll_scrape_job(&mut self, ctx: &egui::Context) -> bool {
+        let Some(ActivePromise::Job(job)) = &mut self.scrape_promise else {
+            return false;
+        };
+        let now = ctx.input(|i| i.time);
+        let Some(request) = &job.request else {
+            if now >= job.next_poll_at {
+                if let Some(job_id) = job.job_id {
+                    let request = ehttp::Request::get(format!("{}/jobs/{}", self.backend_urls.firecrowl, job_id));
+                    job.request = Some(spawn_scrape_promise(ctx, request, self.max_display_bytes, parse_json_response::<JobStatus>));
+                }
+            } else {
+                ctx.request_repaint_after_secs((job.next_poll_at - now) as f32);
+            }
+            return false;
+        };
+        let status = match request.ready() {
+            Some(Ok(status)) => status.clone(),
+            Some(Err(e)) => {
+                let message = format!("{}", e);
+                log::error!("Scrape job failed: {}", message);
+                self.show_scrape_error(message);
+                return true;
+            }
+            None => return false,
+        };
+
+        match status.status.as_str() {
+            "done" => match status.result {
+                Some(response) => self.show_result(ScrapeResult::Firecrowl(response)),
+                None => self.show_scrape_error(format!("Job #{} finished without a result", status.id)),
+            },
+            "failed" => {
+                let error = status.error.unwrap_or_else(|| "unknown error".to_string());
+                log::error!("Scrape job {} failed: {}", status.id, error);
+                self.show_scrape_error(format!("Job #{} failed: {}", status.id, error));
+            }
+            _ => {
+                // Still queued or running: check again later, a little less often each time
+                job.job_id = Some(status.id);
+                job.status = status.status;
+                job.request = None;
+                job.next_poll_at = now + job.poll_interval;
+                job.poll_interval = (job.poll_interval * 2.0).min(JOB_POLL_MAX_SECS);
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Replaces the displayed result with an error message.
+    fn show_scrape_error(&mut self, message: String) {
+        self.error_message = Some(match self.fallback_reason.take() {
+            Some(reason) => format!("Firecrowl failed ({}); the local fallback failed too: {}", reason, message),
+            None => message,
+        });
+        self.markdown_content = None;
+        self.selected_history_index = None;
+        self.is_displaying_result = false;
+    }
+
+    /// Shows the selected item's full Markdown or readability article, following
+    /// `prefer_readability`. A version that hasn't been fetched yet is requested
+    /// from the backend, which usually has it cached.
+    fn show_preferred_version(&mut self, ctx: &egui::Context) {
+        let Some(item) = self.selected_history_item() else { return };
+        let Some(id) = item.id else { return };
+        if let Some(content) = item.versions.get(self.prefer_readability) {
+            self.markdown_content = Some(content.clone());
+            self.show_mobile = false;
+            return;
+        }
+
+        let request_body = serde_json::json!({
+            "url": item.url,
+            "readability": self.prefer_readability,
+            "exclude_images": item.images_stripped,
+        });
+        log::info!("Fetching the {} version of {}", if self.prefer_readability { "readability" } else { "full" }, item.url);
+        let request = self.scrape_request(&request_body);
+        let promise = spawn_scrape_promise(ctx, request, self.max_display_bytes, parse_json_response::<FirecrowlScrapeResponse>);
+        self.version_fetch = Some(VersionFetch { item_id: id, readability: self.prefer_readability, promise });
+    }
+
+    /// Stores a fetched full/readability version and shows it if its item is still selected.
+    fn poll_version_fetch(&mut self, ctx: &egui::Context) {
+        let Some(VersionFetch { item_id: id, readability, promise }) = self.version_fetch.take() else {
+            return;
+        };
+        match promise.try_take() {
+            Ok(Ok(response)) => {
+                // A backend without readability support answers with the full Markdown
+                let article_found = response.readability.or(readability.then_some(false));
+                if let Some(item) = self.scrape_history.iter_mut().find(|item| item.id == Some(id)) {
+                    item.versions.record(response.content, article_found);
+                }
+                if self.selected_history_item().is_some_and(|item| item.id == Some(id)) {
+                    self.show_preferred_version(ctx);
+                }
+            }
+            Ok(Err(e)) => {
+                log::error!("Failed to fetch the other version of item {}: {}", id, e);
+                self.error_message = Some(format!("Failed to fetch the other version: {}", e));
+            }
+            Err(promise) => {
+                self.version_fetch = Some(VersionFetch { item_id: id, readability, promise });
+                ctx.request_repaint();
+            }
+        }
+    }
+
+    /// Clears the displayed result and brings back the input row.
+    fn new_scrape(&mut self) {
+        self.input_url.clear();
+        self.markdown_content = None;
+        self.error_message = None;
+        self.selected_history_index = None;
+        self.is_displaying_result = false;
+        self.focus_url_input = true;
+    }
+
+    /// Saves the displayed result as a Markdown file named after its URL.
+    fn export_current_markdown(&self) {
+        if let Some(content) = &self.markdown_content {
+            let filename = self.selected_history_item().map_or("scraped_content.md".to_string(), |item| export_filename(&item.url, "md"));
+            export_markdown(&filename, content, self.export_wrap_width);
+        }
+    }
+
+    /// Saves the displayed result as pretty-printed JSON: its URL, title,
+    /// word count and Markdown.
+    fn export_current_json(&self) {
+        let Some(content) = &self.markdown_content else { return };
+        let item = self.selected_history_item();
+        let export = ResultExport {
+            url: item.map(|item| item.url.as_str()),
+            title: item.and_then(|item| item.page_title.as_deref()),
+            scraped_at: item.and_then(|item| item.scraped_at.as_deref()),
+            // Counted here when the scraper didn't report it
+            word_count: item.and_then(|item| item.word_count).unwrap_or_else(|| content.split_whitespace().count()),
+            content,
+        };
+        match serde_json::to_string_pretty(&export) {
+            Ok(json) => {
+                let filename = item.map_or("scraped_content.json".to_string(), |item| export_filename(&item.url, "json"));
+                save_text_file(&filename, &json, "json");
+            }
+            Err(e) => log::error!("Failed to serialize the result: {}", e),
+        }
+    }
+
+    /// The history item currently selected, if any.
+    fn selected_history_item(&self) -> Option<&HistoryItem> {
+        self.selected_history_index.and_then(|i| self.scrape_history.get(i))
+    }
+
+    /// The current content layout, which HTML and PDF exports reproduce.
+    fn export_style(&self, style: &egui::Style) -> ExportStyle {
+        let body_size = style.text_styles.get(&egui::TextStyle::Body).map_or(12.5, |font| font.size);
+        ExportStyle {
+            font_size: body_size * self.content_font_scale,
+            content_width: self.content_width,
+        }
+    }
+
+    /// The two history indices to diff, older item first, when compare mode has a full selection.
+    fn compare_pair(&self) -> Option<(usize, usize)> {
+        match self.compare_selection[..] {
+            [a, b] if self.compare_mode => Some((a.min(b), a.max(b))),
+            _ => None,
+        }
+    }
+
+    /// Removes a history item, moving it to the backend's trash when it has an id.
+    /// The item is kept so the Undo toast can put it back.
+    fn delete_history_item(&mut self, ctx: &egui::Context, index: usize) {
+        let item = self.scrape_history.remove(index);
+        log::info!("Deleting history item {}: {}", index, item.url);
+        if let Some(id) = item.id {
+            let request = ehttp::Request {
+                method: "DELETE".to_owned(),
+                ..ehttp::Request::get(format!("{}/history/{}", self.backend_urls.firecrowl, id))
+            };
+            send_history_request(self.with_api_key(request), "delete");
+        }
+
+        match self.selected_history_index {
+            Some(selected) if selected == index => {
+                self.selected_history_index = None;
+                self.markdown_content = None;
+                self.is_displaying_result = false;
+            }
+            Some(selected) if selected > index => self.selected_history_index = Some(selected - 1),
+            _ => {}
+        }
+        self.compare_selection.clear();
+        self.last_deleted = Some(DeletedHistoryItem {
+            index,
+            item,
+            deleted_at: ctx.input(|i| i.time),
+        });
+    }
+
+    /// Puts the most recently deleted item back where it was and restores it on the backend.
+    fn undo_delete(&mut self) {
+        let Some(deleted) = self.last_deleted.take() else { return };
+        log::info!("Restoring history item: {}", deleted.item.url);
+        if let Some(id) = deleted.item.id {
+            let request = ehttp::Request::post(format!("{}/history/{}/restore", self.backend_urls.firecrowl, id), Vec::new());
+            send_history_request(self.with_api_key(request), "restore");
+        }
+        let index = deleted.index.min(self.scrape_history.len());
+        if let Some(selected) = self.selected_history_index.filter(|&selected| selected >= index) {
+            self.selected_history_index = Some(selected + 1);
+        }
+        self.compare_selection.clear();
+        self.scrape_history.insert(index, deleted.item);
+    }
+
+    /// Fetches the backend's trash for the trash view.
+    fn refresh_trash(&mut self, ctx: &egui::Context) {
+        let request = ehttp::Request::get(format!("{}/trash", self.backend_urls.firecrowl));
+        self.trash_promise = Some(spawn_scrape_promise(ctx, request, self.max_display_bytes, parse_json_response::<Vec<StoredItem>>));
+    }
+
+    /// Applies finished trash list / restore requests.
+    fn poll_trash_promises(&mut self, ctx: &egui::Context) {
+        if let Some(promise) = self.trash_promise.take() {
+            match promise.try_take() {
+                Ok(Ok(items)) => self.trash_items = items,
+                Ok(Err(e)) => {
+                    log::error!("Failed to load the trash: {}", e);
+                    self.error_message = Some(format!("Failed to load the trash: {}", e));
+                }
+                Err(promise) => {
+                    self.trash_promise = Some(promise);
+                    ctx.request_repaint();
+                }
+            }
+        }
+        if let Some(promise) = self.restore_promise.take() {
+            match promise.try_take() {
+                Ok(Ok(item)) => {
+                    log::info!("Restored item {} from the trash", item.id);
+                    self.trash_items.retain(|trashed| trashed.id != item.id);
+                    self.scrape_history.push(HistoryItem {
+                        id: Some(item.id),
+                        url: item.url,
+                        page_title: item.title,
+                        versions: ContentVersions { full: Some(item.content.clone()), ..Default::default() },
+                        markdown: item.content,
+                        mobile_markdown: None,
+                        images_stripped: item.images_stripped,
+                        fallback_reason: None,
+                        scraped_at: None,
+                        word_count: None,
+                        html: None,
+                        links: None,
+                    });
+                }
+                Ok(Err(e)) => {
+                    log::error!("Failed to restore item: {}", e);
+                    self.error_message = Some(format!("Failed to restore item: {}", e));
+                }
+                Err(promise) => {
+                    self.restore_promise = Some(promise);
+                    ctx.request_repaint();
+                }
+            }
+        }
+    }
+
+    /// The top bar's "⚙" menu for the backend URLs. Edits are validated and
+    /// only take effect on Save.
+    fn backend_settings_menu(&mut self, ui: &mut egui::Ui) {
+        let menu = ui.menu_button("⚙", |ui| {
+            let (draft, error) = self.backend_urls_draft.get_or_insert_with(|| (self.backend_urls.clone(), None));
+            egui::Grid::new("backend_urls").num_columns(2).show(ui, |ui| {
+                ui.label("Firecrowl URL:");
+                ui.text_edit_singleline(&mut draft.firecrowl);
+                ui.end_row();
+                ui.label("LLM Scraper URL:");
+                ui.text_edit_singleline(&mut draft.llm_scraper);
+                ui.end_row();
+            });
+            if let Some(error) = error {
+                ui.colored_label(egui::Color32::RED, error.as_str());
+            }
+            let (save, reset) = ui.horizontal(|ui| (ui.button("Save").clicked(), ui.button("Reset to defaults").clicked())).inner;
+            if reset {
+                *draft = BackendUrls::default();
+                *error = None;
+            }
+            if save {
+                match draft.validated() {
+                    Ok(urls) => {
+                        log::info!("Backend URLs set to {:?}", urls);
+                        self.backend_urls = urls;
+                        self.backend_urls_draft = None;
+                        ui.close_menu();
+                    }
+                    Err(e) => *error = Some(e),
+                }
+            }
+        });
+        if menu.inner.is_none() {
+            // Closing the menu without saving drops the edits
+            self.backend_urls_draft = None;
+        }
+    }
+
+    /// Starts exporting the whole history into one Markdown file.
+    fn start_export_all(&mut self) {
+        let items: Vec<ExportEntry> = self
+            .scrape_history
+            .iter()
+            .map(|item| ExportEntry { url: item.url.clone(), id: item.id, markdown: item.markdown.clone() })
+            .collect();
+        let count = items.len();
+        log::info!("Exporting {} history items", count);
+        self.export_all_report = None;
+        self.export_all = Some(ExportAll {
+            items,
+            pending: (0..count).collect(),
+            in_flight: Vec::new(),
+            contents: vec![None; count],
+            failures: Vec::new(),
+        });
+    }
+
+    /// Advances an "Export all": collects finished fetches, starts new ones up
+    /// to the concurrency limit, and saves the file once every item is ready.
+    fn poll_export_all(&mut self, ctx: &egui::Context) {
+        let Some(export) = &mut self.export_all else {
+            return;
+        };
+
+        for (index, promise) in std::mem::take(&mut export.in_flight) {
+            match promise.try_take() {
+                Ok(Ok(item)) => export.contents[index] = Some(item.content),
+                Ok(Err(e)) => {
+                    let item = &export.items[index];
+                    log::warn!("Export: couldn't fetch {} ({}); using the copy in history", item.url, e);
+                    export.failures.push(format!("{}: {}", item.url, e));
+                    export.contents[index] = Some(item.markdown.clone());
+                }
+                Err(promise) => export.in_flight.push((index, promise)),
+            }
+        }
+
+        while export.in_flight.len() < self.export_concurrency.max(1) {
+            let Some(index) = export.pending.pop_front() else {
+                break;
+            };
+            let item = &export.items[index];
+            match item.id {
+                Some(id) => {
+                    let request = ehttp::Request::get(format!("{}/history/{}", self.backend_urls.firecrowl, id));
+                    let promise = spawn_scrape_promise(ctx, request, self.max_display_bytes, parse_json_response::<StoredItem>);
+                    export.in_flight.push((index, promise));
+                }
+                None => export.contents[index] = Some(item.markdown.clone()),
+            }
+        }
+
+        if !export.pending.is_empty() || !export.in_flight.is_empty() {
+            ctx.request_repaint();
+            return;
+        }
+        let Some(export) = self.export_all.take() else {
+            return;
+        };
+        let total = export.items.len();
+        let document = export
+            .items
+            .iter()
+            .zip(export.contents)
+            .map(|(item, content)| format!("## {}\n\n{}\n", item.url, content.unwrap_or_default().trim_end()))
+            .collect::<Vec<_>>()
+            .join("\n---\n\n");
+        save_text_file("history.md", &document, "md");
+
+        self.export_all_report = Some(if export.failures.is_empty() {
+            format!("Exported {} items.", total)
+        } else {
+            format!(
+                "Exported {} items; {} couldn't be fetched and use the copy in history:\n{}",
+                total,
+                export.failures.len(),
+                export.failures.join("\n")
+            )
+        });
+    }
+
+    /// Trash view for the history panel: one row per trashed item with a Restore button.
+    fn show_trash_list(&mut self, ui: &mut egui::Ui) {
+        if self.trash_promise.is_some() {
+            ui.add(egui::Spinner::new());
+            return;
+        }
+        let mut restore_id = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            if self.trash_items.is_empty() {
+                ui.label("(Trash is empty)");
+            }
+            for item in &self.trash_items {
+                ui.horizontal(|ui| {
+                    let restore = ui.add_enabled(self.restore_promise.is_none(), egui::Button::new("♻").small());
+                    if restore.on_hover_text("Restore to history").clicked() {
+                        restore_id = Some(item.id);
+                    }
+                    let label = ui.label(url_host(&item.url));
+                    if let Some(deleted_at) = &item.deleted_at {
+                        label.on_hover_text(format!("{}\nDeleted {}", item.url, deleted_at));
+                    }
+                });
+            }
+        });
+        if let Some(id) = restore_id {
+            let request = ehttp::Request::post(format!("{}/history/{}/restore", self.backend_urls.firecrowl, id), Vec::new());
+            let request = self.with_api_key(request);
+            self.restore_promise = Some(spawn_scrape_promise(ui.ctx(), request, self.max_display_bytes, parse_json_response::<StoredItem>));
+        }
+    }
+
+    /// Shows "Deleted … Undo" in the bottom-right corner for a few seconds after a deletion.
+    fn show_undo_toast(&mut self, ctx: &egui::Context) {
+        let Some(deleted) = &self.last_deleted else { return };
+        let remaining = UNDO_TOAST_SECS - (ctx.input(|i| i.time) - deleted.deleted_at);
+        if remaining <= 0.0 {
+            self.last_deleted = None;
+            return;
+        }
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64(remaining));
+
+        let mut undo = false;
+        egui::Area::new(egui::Id::new("undo_toast"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -80.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Deleted {}", deleted.item.url));
+                        undo = ui.button("Undo").clicked();
+                    });
+                });
+            });
+        if undo {
+            self.undo_delete();
+        }
+    }
+
+    /// Central panel contents before anything is scraped: a welcome on first
+    /// run, then example URLs and tips unless the user has hidden them.
+    fn show_empty_state(&mut self, ui: &mut egui::Ui) {
+        if self.first_run {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                ui.heading("👋 Welcome");
+                ui.label("1. Enter or paste a URL in the field below.");
+                ui.label("2. Pick a scraper next to it.");
+                ui.label("3. Click Scrape. Results are kept in the History panel on the left.");
+                if ui.button("Got it").clicked() {
+                    self.first_run = false;
+                }
+            });
+            ui.add_space(10.0);
+        }
+
+        if !self.show_start_hints {
+            ui.label("Scraped content will appear here...");
+            ui.label("Enter a URL below and click Scrape.");
+            return;
+        }
+        ui.label("Scraped content will appear here. Try one of these:");
+        for url in EXAMPLE_URLS {
+            if ui.link(*url).on_hover_text("Put this URL in the input").clicked() {
+                self.input_url = url.to_string();
+            }
+        }
+        ui.add_space(8.0);
+        ui.strong("Scrapers");
+        ui.label(format!("• {}: fetched by the backend through Firecrawl. Results are stored, so repeat scrapes are instant.", ScraperType::Firecrowl));
+        ui.label(format!("• {}: a summary of the page written by the LLM service.", ScraperType::LLM));
+        ui.label(format!("• {}: converted in the app itself, with no backend needed.", ScraperType::Local));
+        ui.add_space(8.0);
+        ui.strong("Shortcuts");
+        ui.label("• Enter in the URL field starts the scrape.");
+        ui.label("• With \"Scrape on paste\" on (⚙ Options), pasting a full URL starts it right away.");
+        ui.label("• Clicking Queue while a scrape runs lines up the next URL.");
+        ui.add_space(8.0);
+        if ui.small_button("Hide these hints").on_hover_text("Turn them back on under ⚙ Options").clicked() {
+            self.show_start_hints = false;
         }
     }
-}
 
-impl TemplateApp {
-    /// Called once before the first frame.
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        Default::default()
+    /// Number of scrapes currently in flight and waiting in the queue.
+    fn scrape_counts(&self) -> (usize, usize) {
+        let in_progress = usize::from(self.scrape_promise.is_some());
+        (in_progress, self.scrape_queue.len())
     }
 }
 
@@ -183,101 +1463,85 @@ impl eframe::App for TemplateApp {
 
     /// Called each time the UI needs repainting.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Taken before any widget sees the keys, so the URL field doesn't act on them too
+        let (scrape_shortcut, new_scrape_shortcut, export_shortcut) = ctx.input_mut(|i| {
+            (
+                i.consume_shortcut(&SCRAPE_SHORTCUT),
+                i.consume_shortcut(&NEW_SCRAPE_SHORTCUT),
+                i.consume_shortcut(&EXPORT_SHORTCUT),
+            )
+        });
+        if new_scrape_shortcut {
+            self.new_scrape();
+        }
+        if export_shortcut && self.is_displaying_result {
+            self.export_current_markdown();
+        }
+
         // --- Handle Promise Resolution (Revised Logic) ---
-        let mut promise_finished = false;
-        if let Some(active_promise) = &self.scrape_promise {
-            match active_promise {
-                ActivePromise::Firecrowl(promise) => {
-                    if let Some(result_ref) = promise.ready() {
-                        match result_ref {
-                            Ok(response_ref) => {
-                                // --- Success Case (Firecrowl) ---
-                                let history_item = HistoryItem {
-                                    url: response_ref.url.clone(),
-                                    markdown: response_ref.content.clone(),
-                                };
-                                self.markdown_content = Some(response_ref.content.clone());
-                                self.error_message = None;
-                                self.is_displaying_result = true;
-                                if self.scrape_history.last().map_or(true, |last| last.url != history_item.url) {
-                                    self.scrape_history.push(history_item);
-                                }
-                                self.selected_history_index = Some(self.scrape_history.len() - 1);
-                                // --- End Success Case ---
-                            }
-                            Err(error_ref) => {
-                                // --- Error Case ---
-                                log::error!("Scraping failed (Firecrowl): {}", error_ref);
-                                self.error_message = Some(format!("{}", error_ref));
-                                self.markdown_content = None;
-                                self.selected_history_index = None;
-                                self.is_displaying_result = false;
-                                // --- End Error Case ---
-                            }
-                        }
-                        promise_finished = true;
+        let mut promise_finished = self.poll_scrape_job(ctx);
+        let outcome = self.scrape_promise.as_ref().and_then(ActivePromise::finished_outcome);
+        if let Some((scraper, outcome)) = outcome {
+            let mut fallback_running = false;
+            match outcome {
+                Ok(result) => {
+                    if let ScrapeResult::Local { url, markdown } = &result {
+                        let ttl_secs = self.local_cache_ttl_mins * 60;
+                        self.local_cache.insert(url, markdown.clone(), ttl_secs);
                     }
+                    self.show_result(result);
                 }
-                ActivePromise::Llm(promise) => {
-                    if let Some(result_ref) = promise.ready() {
-                         match result_ref {
-                            Ok(api_resp_ref) => {
-                                // Extract the inner LlmScrapeResponse
-                                match &api_resp_ref.data {
-                                    Some(llm_resp_ref) => {
-                                        // --- Success Case (LLM) ---
-                                        let history_item = HistoryItem {
-                                            url: llm_resp_ref.url.clone(),
-                                            markdown: llm_resp_ref.summary.clone(), // Use summary field
-                                        };
-                                        self.markdown_content = Some(llm_resp_ref.summary.clone());
-                                        self.error_message = None;
-                                        self.is_displaying_result = true;
-                                        if self.scrape_history.last().map_or(true, |last| last.url != history_item.url) {
-                                            self.scrape_history.push(history_item);
-                                        }
-                                        self.selected_history_index = Some(self.scrape_history.len() - 1);
-                                        // --- End Success Case ---
-                                    }
-                                    None => {
-                                        // --- Error Case (API OK, but no data) ---
-                                        let err_msg = format!("LLM API Response successful but data field is None. Meta: {:?}", api_resp_ref.meta);
-                                        log::error!("{}", err_msg);
-                                        self.error_message = Some("API returned success but no data".to_string());
-                                        self.markdown_content = None;
-                                        self.selected_history_index = None;
-                                        self.is_displaying_result = false;
-                                        // --- End Error Case ---
-                                    }
-                                }
-                            }
-                            Err(error_ref) => {
-                                // --- Error Case ---
-                                log::error!("Scraping failed (LLM): {}", error_ref);
-                                self.error_message = Some(format!("{}", error_ref));
-                                self.markdown_content = None;
-                                self.selected_history_index = None;
-                                self.is_displaying_result = false;
-                                // --- End Error Case ---
-                            }
+                Err(message) => {
+                    log::error!("Scraping failed ({}): {}", scraper, message);
+                    let fallback_url = match &self.scrape_promise {
+                        Some(ActivePromise::Firecrowl(url, _)) if self.local_fallback => Some(url.clone()),
+                        _ => None,
+                    };
+                    match fallback_url {
+                        Some(url) => {
+                            log::warn!("Falling back to the Local scraper for {}", url);
+                            self.scrape_promise = None;
+                            self.fallback_reason = Some(message);
+                            self.start_scrape(ctx, url, ScraperType::Local);
+                            // Unless it was answered from the local cache
+                            fallback_running = self.scrape_promise.is_some();
                         }
-                        promise_finished = true;
+                        None => self.show_scrape_error(message),
                     }
                 }
             }
+            promise_finished = !fallback_running;
+        }
+
+        // Give up on a scrape that has run past its scraper's timeout. The request
+        // itself can't be cancelled; its result is simply ignored.
+        if let Some((deadline, scraper)) = self.scrape_deadline.filter(|_| self.scrape_promise.is_some() && !promise_finished) {
+            if ctx.input(|i| i.time) >= deadline {
+                let timeout = self.scrape_timeouts.get(scraper);
+                log::warn!("{} scrape timed out after {}s", scraper, timeout);
+                self.show_scrape_error(format!("{} scrape timed out after {}s", scraper, timeout));
+                promise_finished = true;
+            }
         }
 
-        // Clear the promise state if it finished in this frame
+        // Clear the promise state if it finished in this frame, then start the next queued scrape
         if promise_finished {
+            self.scrape_deadline = None;
+            self.scrape_started_at = None;
             self.scrape_promise = None;
+            if let Some((url, scraper)) = self.scrape_queue.pop_front() {
+                self.start_scrape(ctx, url, scraper);
+            }
         }
         // --- End Handle Promise Resolution ---
 
         // Determine if currently loading by checking the inner promise
         let is_loading = self.scrape_promise.as_ref().map_or(false, |active_promise| {
             match active_promise {
-                ActivePromise::Firecrowl(promise) => promise.ready().is_none(),
+                ActivePromise::Firecrowl(_, promise) => promise.ready().is_none(),
                 ActivePromise::Llm(promise) => promise.ready().is_none(),
+                ActivePromise::Local(_, promise) => promise.ready().is_none(),
+                ActivePromise::Job(_) => true, // Finished jobs are cleared in the same frame
             }
         });
 
@@ -285,6 +1549,9 @@ impl eframe::App for TemplateApp {
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 ui.heading("Ruscraper");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    self.backend_settings_menu(ui);
+                });
             });
         });
 
@@ -293,34 +1560,154 @@ impl eframe::App for TemplateApp {
             .resizable(false)
             .default_width(300.0)
             .show(ctx, |ui| {
-                ui.heading("History");
+                ui.horizontal(|ui| {
+                    ui.heading("History");
+                    if ui.selectable_label(self.compare_mode, "Compare")
+                        .on_hover_text("Select two items to see a diff of their content")
+                        .clicked()
+                    {
+                        self.compare_mode = !self.compare_mode;
+                        self.compare_selection.clear();
+                    }
+                    if ui.selectable_label(self.show_trash, "🗑 Trash")
+                        .on_hover_text("Items deleted on the Firecrowl backend")
+                        .clicked()
+                    {
+                        self.show_trash = !self.show_trash;
+                        if self.show_trash {
+                            self.refresh_trash(ctx);
+                        }
+                    }
+                    ui.menu_button("⚙", |ui| {
+                        ui.label("Label rows by:");
+                        for format in HistoryLabel::ALL {
+                            if ui.radio_value(&mut self.history_label, format, format.to_string()).clicked() {
+                                ui.close_menu();
+                            }
+                        }
+                        ui.checkbox(&mut self.history_hides_input, "Hide input when selecting")
+                            .on_hover_text("Selecting an item shows only the \"New\" button below; off keeps the URL input ready for another scrape");
+                        ui.separator();
+                        let can_export = self.export_all.is_none() && !self.scrape_history.is_empty();
+                        if ui.add_enabled(can_export, egui::Button::new("⬇ Export all")).clicked() {
+                            self.start_export_all();
+                            ui.close_menu();
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label("Parallel fetches:");
+                            ui.add(egui::DragValue::new(&mut self.export_concurrency).range(1..=16));
+                        });
+                    }).response.on_hover_text("History display and export options");
+                });
+                if let Some(export) = &self.export_all {
+                    let (done, total) = export.progress();
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.add(egui::ProgressBar::new(done as f32 / total.max(1) as f32).text(format!("Exporting {}/{}", done, total)));
+                    });
+                }
+                if let Some(report) = &self.export_all_report {
+                    let mut dismiss = false;
+                    ui.horizontal(|ui| {
+                        ui.small(report.as_str());
+                        dismiss = ui.small_button("✖").on_hover_text("Dismiss").clicked();
+                    });
+                    if dismiss {
+                        self.export_all_report = None;
+                    }
+                }
                 ui.add_space(10.0);
 
+                if self.show_trash {
+                    self.show_trash_list(ui);
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.history_filter)
+                            .hint_text("Filter by URL...")
+                            .desired_width(ui.available_width() - 110.0),
+                    );
+                    if !self.history_filter.is_empty() && ui.small_button("✖").on_hover_text("Clear filter").clicked() {
+                        self.history_filter.clear();
+                    }
+                    ui.checkbox(&mut self.history_filter_content, "Content")
+                        .on_hover_text("Also match the scraped Markdown");
+                });
+                ui.add_space(5.0);
+
+                // Indices stay positions in `scrape_history`, so selection and deletion are unaffected
+                let needle = self.history_filter.trim().to_lowercase();
+                let shown: Vec<usize> = (0..self.scrape_history.len())
+                    .rev()
+                    .filter(|&i| needle.is_empty() || self.scrape_history[i].matches(&needle, self.history_filter_content))
+                    .collect();
+
+                let mut delete_index = None;
+                let mut clicked_row = false;
                 egui::ScrollArea::vertical().show(ui, |ui| {
                     if self.scrape_history.is_empty() {
                         ui.label("(No history yet)");
+                    } else if shown.is_empty() {
+                        ui.label("(no matches)");
                     } else {
-                        for i in (0..self.scrape_history.len()).rev() {
+                        for i in shown {
                             let item = &self.scrape_history[i];
-                            let display_url = item.url.splitn(4, '/').nth(2).unwrap_or(&item.url).to_string();
-                            let label_text = format!("{}: {}", i + 1, display_url);
+                            let label_text = self.history_label.label(i, item);
                             let is_selected = self.selected_history_index == Some(i);
 
                             ui.horizontal(|ui| {
-                                if ui.selectable_label(is_selected, label_text).clicked() {
+                                if self.compare_mode {
+                                    let mut checked = self.compare_selection.contains(&i);
+                                    if ui.checkbox(&mut checked, label_text).changed() {
+                                        if checked {
+                                            // Keep at most two picks, dropping the oldest
+                                            if self.compare_selection.len() == 2 {
+                                                self.compare_selection.remove(0);
+                                            }
+                                            self.compare_selection.push(i);
+                                        } else {
+                                            self.compare_selection.retain(|&selected| selected != i);
+                                        }
+                                    }
+                                    return;
+                                }
+                                let row = ui.selectable_label(is_selected, label_text).on_hover_ui(|ui| {
+                                    ui.set_max_width(400.0);
+                                    ui.label(egui::RichText::new(preview_text(&item.markdown, HOVER_PREVIEW_CHARS)).small());
+                                });
+                                if row.clicked() {
                                     self.selected_history_index = Some(i);
                                     self.markdown_content = Some(item.markdown.clone());
+                                    self.show_mobile = false;
                                     self.error_message = None;
                                     self.input_url = item.url.clone();
+                                    self.is_displaying_result = true;
+                                    self.keep_input_row = !self.history_hides_input;
+                                    clicked_row = true;
+                                }
+                                if ui
+                                    .add_enabled(!item.markdown.is_empty(), egui::Button::new("MD").small())
+                                    .on_hover_text("Export Markdown")
+                                    .clicked()
+                                {
+                                    export_markdown(&export_filename(&item.url, "md"), &item.markdown, self.export_wrap_width);
                                 }
-                                // NYI Buttons
-                                ui.add_enabled(false, egui::Button::new("MD").small()).on_hover_text("Export Markdown (NYI)");
                                 ui.add_enabled(false, egui::Button::new("PDF").small()).on_hover_text("Export PDF (NYI)");
-                                ui.add_enabled(false, egui::Button::new("🗑").small()).on_hover_text("Delete History Item (NYI)");
+                                if ui.add(egui::Button::new("🗑").small()).on_hover_text("Delete history item").clicked() {
+                                    delete_index = Some(i);
+                                }
                             });
                         }
                     }
                 });
+                if let Some(index) = delete_index {
+                    self.delete_history_item(ctx, index);
+                }
+                if clicked_row {
+                    self.show_preferred_version(ctx);
+                }
             });
 
         // --- Bottom Panel (Input/Controls/Error) ---
@@ -340,17 +1727,13 @@ impl eframe::App for TemplateApp {
                     log::info!("Bottom panel redraw: is_displaying_result = {}", self.is_displaying_result);
 
                     // Show EITHER the "New" button OR the input row
-                    if self.is_displaying_result {
+                    if self.is_displaying_result && !self.keep_input_row {
                         // Wrap "New +" button in a horizontal layout for consistent padding
                         ui.horizontal(|ui| {
                             let new_button = egui::Button::new("➕ New").min_size(egui::vec2(100.0, 35.0));
-                            if ui.add(new_button).clicked() {
-                                // Reset state for a new scrape
-                                self.input_url.clear();
-                                self.markdown_content = None;
-                                self.error_message = None;
-                                self.selected_history_index = None;
-                                self.is_displaying_result = false;
+                            let new_hint = format!("Start a new scrape ({})", ctx.format_shortcut(&NEW_SCRAPE_SHORTCUT));
+                            if ui.add(new_button).on_hover_text(new_hint).clicked() {
+                                self.new_scrape();
                             }
                         });
                     } else {
@@ -359,21 +1742,47 @@ impl eframe::App for TemplateApp {
                             let available_width = ui.available_width();
                             let button_width = 100.0;
                             let combo_width = 120.0;
-                            let spacing = ui.spacing().item_spacing.x * 2.0;
-                            let desired_input_width = (available_width - button_width - combo_width - spacing).max(50.0);
+                            let options_width = 80.0;
+                            let timeout_width = 70.0;
+                            let formats_width = 70.0;
+                            let spacing = ui.spacing().item_spacing.x * 5.0;
+                            let desired_input_width = (available_width
+                                - button_width
+                                - combo_width
+                                - formats_width
+                                - options_width
+                                - timeout_width
+                                - spacing)
+                                .max(50.0);
                             let widget_height = 35.0;
 
                             // --- URL Input ---
                             let mut trigger_scrape = false;
-                            let url_input_enabled = !is_loading; // Only disable if actively loading
-                            let url_input_response = ui.add_enabled(
-                                url_input_enabled,
+                            let url_input_response = ui.add(
                                 egui::TextEdit::singleline(&mut self.input_url)
                                     .desired_width(desired_input_width)
                                     .min_size(egui::vec2(0.0, widget_height))
                                     .hint_text("Enter URL to scrape..."),
                             );
-                            if url_input_enabled && url_input_response.lost_focus() && ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            if std::mem::take(&mut self.focus_url_input) {
+                                url_input_response.request_focus();
+                            }
+                            if url_input_response.changed() {
+                                self.input_url_error = None;
+                            }
+                            if url_input_response.lost_focus() && ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
+                                trigger_scrape = true;
+                            }
+                            if scrape_shortcut {
+                                trigger_scrape = true;
+                            }
+                            // Auto-scrape only when the paste leaves a complete URL in the field
+                            if self.auto_scrape_on_paste
+                                && url_input_response.changed()
+                                && ctx.input(|i| i.events.iter().any(|e| matches!(e, egui::Event::Paste(_))))
+                                && looks_like_url(&self.input_url)
+                            {
+                                log::info!("URL pasted; scraping automatically");
                                 trigger_scrape = true;
                             }
 
@@ -391,68 +1800,167 @@ impl eframe::App for TemplateApp {
                                             .show_ui(ui, |ui| {
                                                 ui.selectable_value(&mut self.selected_scraper, ScraperType::Firecrowl, ScraperType::Firecrowl.to_string());
                                                 ui.selectable_value(&mut self.selected_scraper, ScraperType::LLM, ScraperType::LLM.to_string());
+                                                ui.selectable_value(&mut self.selected_scraper, ScraperType::Local, ScraperType::Local.to_string())
+                                                    .on_hover_text(LOCAL_SCRAPER_HINT);
                                             });
                                     });
                                 });
                             });
 
-                            // --- Scrape Button ---
-                            let scrape_button_enabled = !is_loading && !self.input_url.trim().is_empty();
-                            let button_text = if is_loading { "..." } else { "Scrape" };
-                            let scrape_button = egui::Button::new(button_text).min_size(egui::vec2(button_width, widget_height));
-                            if ui.add_enabled(scrape_button_enabled, scrape_button).clicked() {
-                                trigger_scrape = true;
-                            }
-
-                            // --- Trigger Scrape Action ---
-                            if trigger_scrape && scrape_button_enabled { // Ensure button *was* enabled
-                                if !self.input_url.trim().is_empty() {
-                                    log::info!("Scrape triggered for URL: {}", self.input_url);
-                                    self.error_message = None;
-                                    self.markdown_content = None;
-                                    self.selected_history_index = None;
+                            // --- Formats (Firecrowl returns Markdown plus any extras picked here) ---
+                            let formats_enabled = !is_loading && self.selected_scraper == ScraperType::Firecrowl;
+                            ui.add_enabled_ui(formats_enabled, |ui| {
+                                ui.menu_button(self.scrape_formats.label(), |ui| {
+                                    ui.add_enabled(false, egui::Checkbox::new(&mut true, "Markdown"))
+                                        .on_disabled_hover_text("Always included");
+                                    ui.checkbox(&mut self.scrape_formats.html, "HTML");
+                                    ui.checkbox(&mut self.scrape_formats.links, "Links");
+                                })
+                                .response
+                                .on_hover_text("Formats to request from Firecrowl");
+                            });
 
-                                    // --- Create and Spawn Promise ---
-                                    let active_promise_enum = match self.selected_scraper {
+                            // --- Scrape Options (per scraper type) ---
+                            ui.add_enabled_ui(!is_loading, |ui| {
+                                ui.menu_button("⚙ Options", |ui| {
+                                    match self.selected_scraper {
                                         ScraperType::Firecrowl => {
-                                            let base_url = FIRECROWL_URL;
-                                            let request_url = format!("{}/scrape", base_url);
-                                            log::info!("Requesting Firecrowl POST scrape to: {}", request_url);
-                                            let request_body = serde_json::json!({ "url": self.input_url });
-                                            let headers = ehttp::Headers::new(&[("Content-Type", "application/json")]);
-                                            let mut request = ehttp::Request::post(request_url, request_body.to_string().into_bytes());
-                                            request.headers = headers;
-
-                                            let promise = spawn_scrape_promise::<FirecrowlScrapeResponse>(ctx, request);
-                                            // Wrap in enum variant
-                                            ActivePromise::Firecrowl(promise)
+                                            ui.checkbox(&mut self.scrape_mobile, "📱 Also scrape mobile viewport");
+                                            ui.checkbox(&mut self.exclude_images, "🖼 Exclude images");
+                                            ui.checkbox(&mut self.prefer_readability, "📖 Readability (main article only)")
+                                                .on_hover_text("Ask for just the page's main article, reader-view style");
+                                            ui.checkbox(&mut self.async_scrape, "⏳ Run as background job")
+                                                .on_hover_text("Queue the scrape on the backend and poll until it's done");
+                                            ui.checkbox(&mut self.local_fallback, "↩ Fall back to Local on failure")
+                                                .on_hover_text("When Firecrowl fails, scrape the page with the Local scraper instead. Doesn't apply to background jobs.");
+                                            ui.horizontal(|ui| {
+                                                ui.label("Language:");
+                                                ui.add(
+                                                    egui::TextEdit::singleline(&mut self.accept_language)
+                                                        .hint_text("e.g. de-DE")
+                                                        .desired_width(90.0),
+                                                );
+                                            });
+                                            ui.horizontal(|ui| {
+                                                ui.label("API key:");
+                                                ui.add(egui::TextEdit::singleline(&mut self.api_key).password(true).desired_width(120.0))
+                                                    .on_hover_text("Needed when the backend is started with API_KEY");
+                                            });
+                                        }
+                                        ScraperType::Local => {
+                                            if cfg!(target_arch = "wasm32") {
+                                                ui.weak(LOCAL_SCRAPER_HINT);
+                                            }
+                                            ui.checkbox(&mut self.bypass_local_cache, "Bypass cache");
+                                            ui.horizontal(|ui| {
+                                                ui.label("Cache TTL:");
+                                                ui.add(egui::DragValue::new(&mut self.local_cache_ttl_mins).range(1..=10_080).suffix(" min"));
+                                            });
                                         }
                                         ScraperType::LLM => {
-                                            let base_url = LLM_SCRAPER_URL;
-                                            let request_url = format!("{}/api/scrape", base_url);
-                                            log::info!("Requesting LLM POST scrape to: {}", request_url);
-                                            let request_body = serde_json::json!({ "url": self.input_url });
-                                            let headers = ehttp::Headers::new(&[("Content-Type", "application/json")]);
-                                            let mut request = ehttp::Request::post(request_url, request_body.to_string().into_bytes());
-                                            request.headers = headers;
-
-                                            let promise = spawn_scrape_promise::<LlmApiResponse<LlmScrapeResponse>>(ctx, request);
-                                            // Wrap in enum variant
-                                            ActivePromise::Llm(promise)
+                                            ui.label("Summary instructions (optional):");
+                                            ui.add(
+                                                egui::TextEdit::multiline(&mut self.llm_instructions)
+                                                    .hint_text("e.g. 5 bullet points, focus on pricing")
+                                                    .desired_rows(3),
+                                            );
                                         }
+                                    }
+                                    ui.horizontal(|ui| {
+                                        ui.label("Timeout:");
+                                        ui.add(egui::DragValue::new(self.scrape_timeouts.get_mut(self.selected_scraper)).range(5..=600).suffix(" s"));
+                                    });
+                                    ui.horizontal(|ui| {
+                                        ui.label("Slow scrape hint after:");
+                                        ui.add(egui::DragValue::new(&mut self.slow_scrape_hint_secs).range(1..=600).suffix(" s"));
+                                    });
+                                    // The Local scraper runs in-app, so there's no request to reproduce
+                                    let request = match self.selected_scraper {
+                                        ScraperType::Firecrowl => Some(self.firecrowl_request(&self.input_url)),
+                                        ScraperType::LLM => Some(self.llm_request(&self.input_url)),
+                                        ScraperType::Local => None,
                                     };
-                                    self.scrape_promise = Some(active_promise_enum);
-                                    // --- End Promise Creation ---
+                                    let copy_button = ui
+                                        .add_enabled(request.is_some() && !self.input_url.trim().is_empty(), egui::Button::new("📋 Copy as curl"))
+                                        .on_hover_text("Copy the request this scrape would send as a curl command")
+                                        .on_disabled_hover_text("Enter a URL; Local scrapes don't send a request");
+                                    if let Some(request) = request.filter(|_| copy_button.clicked()) {
+                                        ui.ctx().copy_text(curl_command(&request));
+                                        ui.close_menu();
+                                    }
+                                    ui.separator();
+                                    ui.checkbox(&mut self.auto_scrape_on_paste, "📋 Scrape on paste")
+                                        .on_hover_text("Start scraping as soon as a full URL is pasted into the input");
+                                    ui.checkbox(&mut self.show_start_hints, "💡 Show start hints")
+                                        .on_hover_text("Example URLs and tips in the empty content area");
+                                });
+                            });
+
+                            // --- Scrape Button (queues behind the active scrape while loading) ---
+                            let scrape_button_enabled = !self.input_url.trim().is_empty();
+                            let button_text = if is_loading { "Queue" } else { "Scrape" };
+                            let scrape_button = egui::Button::new(button_text).min_size(egui::vec2(button_width, widget_height));
+                            let scrape_hint = format!("Scrape the URL (Enter or {})", ctx.format_shortcut(&SCRAPE_SHORTCUT));
+                            if ui.add_enabled(scrape_button_enabled, scrape_button).on_hover_text(scrape_hint).clicked() {
+                                trigger_scrape = true;
+                            }
+                            if self.selected_scraper == ScraperType::Firecrowl && self.async_scrape {
+                                ui.weak("no timeout").on_hover_text("Background jobs are polled until the backend finishes them");
+                            } else {
+                                ui.weak(format!("timeout {}s", self.scrape_timeouts.get(self.selected_scraper)))
+                                    .on_hover_text("Client-side timeout for this scraper; change it under ⚙ Options");
+                            }
+
+                            // --- Trigger Scrape Action ---
+                            // Enter and the button both go through the same check, so obviously
+                            // bad input never reaches the backend
+                            if trigger_scrape && scrape_button_enabled { // Ensure button *was* enabled
+                                match normalize_scrape_url(&self.input_url) {
+                                    Ok(url) => {
+                                        self.input_url = url;
+                                        self.input_url_error = None;
+                                    }
+                                    Err(error) => {
+                                        log::info!("Not scraping {:?}: {}", self.input_url, error);
+                                        self.input_url_error = Some(error);
+                                        trigger_scrape = false;
+                                    }
+                                }
+                            }
+                            if trigger_scrape && scrape_button_enabled {
+                                if is_loading {
+                                    // Clear the input so the next URL can be typed straight away
+                                    let url = std::mem::take(&mut self.input_url);
+                                    log::info!("Queueing scrape for URL: {}", url);
+                                    self.scrape_queue.push_back((url, self.selected_scraper));
                                 } else {
-                                    // This case should be prevented by button enablement, but handle defensively
-                                    self.error_message = Some("Please enter a URL.".to_string());
+                                    self.start_scrape(ctx, self.input_url.clone(), self.selected_scraper);
                                 }
                             }
                         }); // End horizontal layout for input row
+                        if let Some(error) = &self.input_url_error {
+                            ui.colored_label(egui::Color32::RED, error.as_str());
+                        }
                     } // End if/else for is_displaying_result
 
                     // --- Footer Row ---
                     ui.horizontal(|ui| {
+                        // Queue status, refreshed every frame
+                        let (in_progress, queued) = self.scrape_counts();
+                        if in_progress + queued > 0 {
+                            ui.label(format!("{} in progress, {} queued", in_progress, queued));
+                        }
+                        let shortcuts = [
+                            (SCRAPE_SHORTCUT, "Scrape the URL in the input"),
+                            (NEW_SCRAPE_SHORTCUT, "New scrape"),
+                            (EXPORT_SHORTCUT, "Export the result as Markdown"),
+                        ];
+                        let help = shortcuts
+                            .iter()
+                            .map(|(shortcut, action)| format!("{}: {}", ctx.format_shortcut(shortcut), action))
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        ui.weak("⌨ Shortcuts").on_hover_text(help);
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                             ui.add_space(10.0);
                             egui::widgets::global_theme_preference_buttons(ui);
@@ -472,56 +1980,280 @@ impl eframe::App for TemplateApp {
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.heading("Scraped Content");
+                // Size of what's on screen, and the formats this result is available in
+                if let Some(content) = self.markdown_content.as_deref().filter(|_| self.is_displaying_result) {
+                    ui.weak(format_size(content.len()));
+                    format_badge(ui, "MD").on_hover_text("Markdown");
+                    if let Some(item) = self.selected_history_item() {
+                        if item.mobile_markdown.is_some() {
+                            format_badge(ui, "Mobile").on_hover_text("Mobile-viewport Markdown");
+                        }
+                        if let Some(html) = &item.html {
+                            let badge = format_badge(ui, "HTML").interact(egui::Sense::click());
+                            if badge.on_hover_text(format!("Page HTML ({}); click to save", format_size(html.len()))).clicked() {
+                                save_text_file("scraped_page.html", html, "html");
+                            }
+                        }
+                        if let Some(links) = &item.links {
+                            let badge = format_badge(ui, "Links").interact(egui::Sense::click());
+                            if badge.on_hover_text(format!("{} linked URLs; click to copy", links.len())).clicked() {
+                                ui.ctx().copy_text(links.join("\n"));
+                            }
+                        }
+                        if item.fallback_reason.is_some() {
+                            format_badge(ui, "Local fallback").on_hover_text("Scraped locally because Firecrowl failed");
+                        }
+                        if let Some(id) = item.id {
+                            format_badge(ui, "JSON").on_hover_text(format!("Stored by the backend: GET /history/{}", id));
+                        }
+                    }
+                }
+                // Desktop/Mobile toggle when the selected item has a mobile variant
+                let selected_item = self.selected_history_index.and_then(|i| self.scrape_history.get(i));
+                if let Some(item) = selected_item.filter(|_| self.is_displaying_result) {
+                    if let Some(mobile_markdown) = &item.mobile_markdown {
+                        let was_mobile = self.show_mobile;
+                        ui.selectable_value(&mut self.show_mobile, false, "🖥 Desktop");
+                        ui.selectable_value(&mut self.show_mobile, true, "📱 Mobile");
+                        if self.show_mobile != was_mobile {
+                            let content = if self.show_mobile { mobile_markdown } else { &item.markdown };
+                            self.markdown_content = Some(content.clone());
+                        }
+                    }
+                }
+                // Full page / readability article toggle for results stored by the backend
+                let has_versions = self.is_displaying_result && self.selected_history_item().is_some_and(|item| item.id.is_some());
+                if has_versions {
+                    if ui.toggle_value(&mut self.prefer_readability, "📖 Reader")
+                        .on_hover_text("Show the page's main article instead of the full page")
+                        .changed()
+                    {
+                        self.show_preferred_version(ctx);
+                    }
+                    if self.version_fetch.is_some() {
+                        ui.spinner();
+                    } else if self.prefer_readability && self.selected_history_item().is_some_and(|item| item.versions.no_article) {
+                        ui.weak("No article found");
+                    }
+                }
+                // Table view toggle, offered only when the result contains tables
+                let has_tables = self.is_displaying_result
+                    && self.markdown_content.as_deref().is_some_and(|content| {
+                        !tables::extract_tables(truncate_for_display(content, self.max_display_bytes).0).is_empty()
+                    });
+                if has_tables {
+                    ui.toggle_value(&mut self.show_tables, "▦ Tables").on_hover_text("Show the result's tables as sortable grids");
+                } else {
+                    self.show_tables = false;
+                }
                 if self.is_displaying_result {
+                    ui.toggle_value(&mut self.show_source, "</> Source").on_hover_text("Show the Markdown source instead of rendering it");
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         ui.add_space(10.0);
                         // Placeholder Export Buttons
-                        if ui.button("Ⓜ️ MD").on_hover_text("Export as Markdown (NYI)").clicked() {
-                             if let Some(content) = &self.markdown_content {
-                                 save_markdown_file("scraped_content.md", content);
-                             }
+                        let export_hint = format!("Export as Markdown ({})", ctx.format_shortcut(&EXPORT_SHORTCUT));
+                        if ui.button("Ⓜ️ MD").on_hover_text(export_hint).clicked() {
+                            self.export_current_markdown();
+                        }
+                        if ui.button("{ } JSON").on_hover_text("Export as JSON: URL, title, word count and Markdown").clicked() {
+                            self.export_current_json();
+                        }
+                        if ui.button("🌐 HTML").on_hover_text("Export as HTML").clicked() {
+                            if let Some(content) = &self.markdown_content {
+                                let title = self.selected_history_item().map_or("Scraped Content", |item| item.url.as_str());
+                                let html = export::markdown_to_html(title, content, self.export_style(ui.style()));
+                                save_text_file("scraped_content.html", &html, "html");
+                            }
+                        }
+                        if ui.button("📄 PDF").on_hover_text("Export as PDF").clicked() {
+                            if let Some(content) = &self.markdown_content {
+                                save_pdf_file("scraped_content.pdf", content, self.export_style(ui.style()));
+                            }
                         }
-                        if ui.button("📄 PDF").on_hover_text("Export as PDF (NYI)").clicked() {
+                        let has_content = self.markdown_content.as_deref().is_some_and(|content| !content.is_empty());
+                        if ui.add_enabled(has_content, egui::Button::new("📋 Copy")).on_hover_text("Copy the Markdown to the clipboard").clicked() {
                             if let Some(content) = &self.markdown_content {
-                                save_pdf_file("scraped_content.pdf", content);
+                                ui.ctx().copy_text(content.clone());
+                                self.copied_at = Some(ui.input(|i| i.time));
+                            }
+                        }
+                        if let Some(copied_at) = self.copied_at {
+                            let remaining = COPIED_LABEL_SECS - (ui.input(|i| i.time) - copied_at);
+                            if remaining > 0.0 {
+                                ui.weak("Copied!");
+                                ui.ctx().request_repaint_after(std::time::Duration::from_secs_f64(remaining));
+                            } else {
+                                self.copied_at = None;
                             }
                         }
+                        ui.menu_button("⚙", |ui| {
+                            // View settings; HTML and PDF exports follow them
+                            ui.horizontal(|ui| {
+                                ui.label("Text size:");
+                                let mut percent = self.content_font_scale * 100.0;
+                                if ui.add(egui::DragValue::new(&mut percent).range(50.0..=200.0).speed(1.0).suffix("%")).changed() {
+                                    self.content_font_scale = percent / 100.0;
+                                }
+                            });
+                            let mut limit_width = self.content_width.is_some();
+                            if ui.checkbox(&mut limit_width, "Limit content width").changed() {
+                                self.content_width = limit_width.then_some(DEFAULT_CONTENT_WIDTH);
+                            }
+                            if let Some(width) = &mut self.content_width {
+                                ui.horizontal(|ui| {
+                                    ui.label("Width:");
+                                    ui.add(egui::DragValue::new(width).range(300.0..=1600.0).speed(5.0).suffix(" pt"));
+                                });
+                            }
+                            ui.horizontal(|ui| {
+                                ui.label("Display limit:");
+                                let mut kib = self.max_display_bytes / 1024;
+                                if ui.add(egui::DragValue::new(&mut kib).range(64..=16 * 1024).suffix(" KB"))
+                                    .on_hover_text("Longer results are truncated on screen; exports are always complete")
+                                    .changed()
+                                {
+                                    self.max_display_bytes = kib * 1024;
+                                }
+                            });
+                            ui.separator();
+                            let mut safe_mode = self.viewer_filter == ViewerFilter::SAFE;
+                            if ui.checkbox(&mut safe_mode, "Safe reading mode")
+                                .on_hover_text("Text only: no images, links or HTML")
+                                .changed()
+                            {
+                                self.viewer_filter = if safe_mode { ViewerFilter::SAFE } else { ViewerFilter::default() };
+                            }
+                            ui.indent("viewer_filter", |ui| {
+                                ui.checkbox(&mut self.viewer_filter.images, "Show images")
+                                    .on_hover_text("Hidden images aren't downloaded");
+                                ui.checkbox(&mut self.viewer_filter.links, "Show links")
+                                    .on_hover_text("Hidden links are shown as plain text");
+                                ui.checkbox(&mut self.viewer_filter.raw_html, "Show raw HTML");
+                            });
+                            ui.separator();
+                            let mut wrap = self.export_wrap_width.is_some();
+                            if ui.checkbox(&mut wrap, "Wrap exported Markdown").changed() {
+                                self.export_wrap_width = wrap.then_some(DEFAULT_EXPORT_WRAP_WIDTH);
+                            }
+                            if let Some(width) = &mut self.export_wrap_width {
+                                ui.horizontal(|ui| {
+                                    ui.label("Line width:");
+                                    ui.add(egui::DragValue::new(width).range(20..=200).suffix(" cols"));
+                                });
+                            }
+                        }).response.on_hover_text("View and export options");
                     });
                 }
             });
 
+            if self.is_displaying_result && self.selected_history_item().is_some_and(|item| item.images_stripped) {
+                ui.weak("Images were removed from this result.");
+            }
+            if let Some(reason) = self.selected_history_item().and_then(|item| item.fallback_reason.as_deref()).filter(|_| self.is_displaying_result) {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("⚠ Firecrowl failed ({}). This is a best-effort local scrape.", reason),
+                );
+            }
+
             ui.add_space(5.0);
             egui::Frame::group(ui.style()).show(ui, |ui| {
                 egui::ScrollArea::vertical()
                     .stick_to_bottom(true)
                     .auto_shrink([false, false])
                     .show(ui, |ui| {
-                        if is_loading {
-                            ui.add(egui::Spinner::new());
-                            ui.label("Fetching content...");
+                        for font in ui.style_mut().text_styles.values_mut() {
+                            font.size *= self.content_font_scale;
+                        }
+                        if let Some(width) = self.content_width {
+                            ui.set_max_width(width);
+                        }
+                        if let Some((a, b)) = self.compare_pair() {
+                            ui.strong(format!("Comparing #{} ↔ #{}", a + 1, b + 1));
+                            ui.add_space(5.0);
+                            diff_view::show_diff(ui, &self.scrape_history[a].markdown, &self.scrape_history[b].markdown);
+                        } else if is_loading {
+                            let elapsed = self.scrape_started_at.map_or(0.0, |started| ctx.input(|i| i.time) - started);
+                            let cancel = ui.horizontal(|ui| {
+                                ui.add(egui::Spinner::new());
+                                match &self.scrape_promise {
+                                    Some(ActivePromise::Job(job)) => ui.label(job.status_text()),
+                                    _ => ui.label("Fetching content..."),
+                                };
+                                ui.weak(format!("{}s", elapsed as u64));
+                                ui.button("Cancel")
+                                    .on_hover_text("Stop waiting for this scrape. The request may still finish in the background, but its result is ignored.")
+                                    .clicked()
+                            }).inner;
+                            if cancel {
+                                self.cancel_scrape(ctx);
+                            }
+                            if elapsed >= self.slow_scrape_hint_secs as f64 {
+                                ui.weak("This is taking longer than usual. Large or slow pages can take a while.");
+                            }
+                            // Tick the timer once a second
+                            ctx.request_repaint_after(std::time::Duration::from_secs_f64(1.0 - elapsed.fract()));
+                        } else if let Some(content) = self.markdown_content.as_deref().filter(|_| self.show_tables) {
+                            let (shown, _) = truncate_for_display(content, self.max_display_bytes);
+                            tables::show_tables(ui, &tables::extract_tables(shown));
+                        } else if let Some(content) = self.markdown_content.as_deref() {
+                            let (shown, truncated) = truncate_for_display(content, self.max_display_bytes);
+                            if truncated {
+                                ui.horizontal_wrapped(|ui| {
+                                    ui.colored_label(
+                                        ui.visuals().warn_fg_color,
+                                        format!(
+                                            "Content too large ({}), truncated to the first {} (download for full).",
+                                            format_size(content.len()),
+                                            format_size(shown.len())
+                                        ),
+                                    );
+                                    if ui.button("⬇ Download full").clicked() {
+                                        save_text_file("scraped_content.md", content, "md");
+                                    }
+                                });
+                                ui.separator();
+                            }
+                            if self.show_source {
+                                // A &str buffer keeps the editor read-only
+                                ui.add(egui::TextEdit::multiline(&mut &*shown).code_editor().desired_width(f32::INFINITY));
+                            } else {
+                                let display_text = sanitize::filter_markdown(shown, self.viewer_filter);
+                                CommonMarkViewer::new()
+                                    .show(ui, &mut egui_commonmark::CommonMarkCache::default(), &display_text);
+                            }
+                            if let Some(metadata) = self
+                                .selected_history_item()
+                                .filter(|_| self.is_displaying_result)
+                                .and_then(HistoryItem::metadata_line)
+                            {
+                                ui.separator();
+                                ui.weak(metadata);
+                            }
                         } else {
-                            let display_text = self.markdown_content.as_deref()
-                                .unwrap_or("Scraped content will appear here...
-
-Enter a URL below and click Scrape.");
-                            CommonMarkViewer::new()
-                                .show(ui, &mut egui_commonmark::CommonMarkCache::default(), display_text);
+                            self.show_empty_state(ui);
                         }
                     });
             });
         });
+
+        self.poll_trash_promises(ctx);
+        self.poll_version_fetch(ctx);
+        self.poll_export_all(ctx);
+        self.show_undo_toast(ctx);
     } // End update fn
 } // End impl eframe::App
 
 
 // --- Helper function to spawn the scrape promise ---
-// Returns a promise for the direct deserialized type T
+// Returns a promise for the value produced by `parse` from the HTTP response;
+// `max_display_bytes` is the on-screen limit, for warning about large responses
 fn spawn_scrape_promise<T: 'static + Send>(
     _ctx: &egui::Context, // Use underscore for unused parameter
     request: ehttp::Request,
+    max_display_bytes: usize,
+    parse: fn(ehttp::Response) -> Result<T, FrontendError>,
 ) -> Promise<Result<T, FrontendError>> // Return Result<T, FrontendError>
-where
-    T: for<'de> Deserialize<'de> + Clone,
 {
     #[cfg(not(target_arch = "wasm32"))]
     {
@@ -531,30 +2263,8 @@ where
                 ehttp::fetch_async(request_clone)
                     .await
                     .map_err(FrontendError::from)
-                    .and_then(|response| {
-                        if response.ok {
-                            let body_bytes_for_log = response.bytes.clone();
-                            if let Ok(text) = std::str::from_utf8(&body_bytes_for_log) {
-                                log::info!("Attempting to parse JSON response: {}", text);
-                            } else {
-                                log::warn!("Received non-UTF8 response body before parsing.");
-                            }
-
-                            // Attempt to parse directly into T
-                            serde_json::from_slice::<T>(&response.bytes)
-                                .map_err(|e| {
-                                    log::error!("JSON parsing failed: {:?}. Raw response logged above.", e);
-                                    FrontendError::JsonParse(e)
-                                })
-                        } else {
-                            let err_msg = format!(
-                                "API request failed with status {}: {}",
-                                response.status, response.status_text
-                            );
-                            log::error!("{}", err_msg);
-                            Err(FrontendError::ApiError(err_msg))
-                        }
-                    })
+                    .map(|response| warn_if_large(response, max_display_bytes))
+                    .and_then(parse)
             })
         })
     }
@@ -565,60 +2275,306 @@ where
             ehttp::fetch_async(request)
                 .await
                 .map_err(FrontendError::from)
-                .and_then(|response| {
-                    if response.ok {
-                        let body_bytes_for_log = response.bytes.clone();
-                        if let Ok(text) = std::str::from_utf8(&body_bytes_for_log) {
-                            log::info!("Attempting to parse JSON response: {}", text);
-                        } else {
-                            log::warn!("Received non-UTF8 response body before parsing.");
-                        }
+                .map(|response| warn_if_large(response, max_display_bytes))
+                .and_then(parse)
+        })
+    }
+}
 
-                        // Attempt to parse directly into T
-                         serde_json::from_slice::<T>(&response.bytes)
-                            .map_err(|e| {
-                                log::error!("JSON parsing failed: {:?}. Raw response logged above.", e);
-                                FrontendError::JsonParse(e)
-                            })
-                    } else {
-                        let err_msg = format!(
-                            "API request failed with status {}: {}",
-                            response.status, response.status_text
-                        );
-                        log::error!("{}", err_msg);
-                        Err(FrontendError::ApiError(err_msg))
-                    }
-                })
+// Logs responses big enough that the result will be truncated on screen.
+fn warn_if_large(response: ehttp::Response, max_display_bytes: usize) -> ehttp::Response {
+    if response.bytes.len() > max_display_bytes {
+        log::warn!(
+            "Large response from {} ({}); the display may be truncated",
+            response.url,
+            format_size(response.bytes.len())
+        );
+    }
+    response
+}
+
+// Error body sent by the backend with non-OK statuses
+#[derive(Deserialize)]
+struct ApiErrorBody {
+    code: Option<String>,
+    message: String,
+}
+
+// Parses a successful response body as JSON of type T; non-OK statuses become
+// ApiError, with the backend's message when the body is its error JSON
+fn parse_json_response<T>(response: ehttp::Response) -> Result<T, FrontendError>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    if response.ok {
+        // Attempt to parse directly into T
+        serde_json::from_slice::<T>(&response.bytes)
+            .map_err(|e| {
+                let snippet = body_snippet(&String::from_utf8_lossy(&response.bytes), 200);
+                log::error!("JSON parsing failed: {:?}. Response starts with: {}", e, snippet);
+                FrontendError::JsonParse(e, Some(snippet))
+            })
+    } else {
+        let error = match serde_json::from_slice::<ApiErrorBody>(&response.bytes) {
+            Ok(body) => FrontendError::ApiError {
+                code: body.code,
+                message: body.message,
+            },
+            // Not the backend talking, e.g. a proxy's error page
+            Err(_) => {
+                let body = String::from_utf8_lossy(&response.bytes);
+                let received = if body.trim().is_empty() {
+                    String::new()
+                } else if body.trim_start().starts_with('<') {
+                    " (the response was an HTML page, not an error from the service)".to_string()
+                } else {
+                    format!(". Response starts with: \"{}\"", body_snippet(&body, 200))
+                };
+                FrontendError::ApiError {
+                    code: None,
+                    message: format!("request failed with status {}: {}{}", response.status, response.status_text, received),
+                }
+            }
+        };
+        log::error!("{}", error);
+        Err(error)
+    }
+}
+
+// LLM responses get a friendlier parse error that says what came back instead
+fn parse_llm_response(response: ehttp::Response) -> Result<LlmApiResponse<LlmScrapeResponse>, FrontendError> {
+    parse_json_response(response).map_err(|err| match err {
+        FrontendError::JsonParse(e, snippet) => {
+            let snippet = snippet.unwrap_or_default();
+            let received = if snippet.starts_with('<') {
+                "an HTML page instead of JSON"
+            } else if e.classify() == serde_json::error::Category::Data {
+                "JSON that doesn't match the expected structure"
+            } else {
+                "a response that isn't valid JSON"
+            };
+            FrontendError::UnexpectedResponse(format!(
+                "The LLM scraper returned {} ({}). Response starts with: \"{}\"",
+                received,
+                e,
+                snippet
+            ))
+        }
+        other => other,
+    })
+}
+
+// First `max_chars` characters of a body on one line, for error messages
+fn body_snippet(body: &str, max_chars: usize) -> String {
+    let collapsed = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() > max_chars {
+        format!("{}…", collapsed.chars().take(max_chars).collect::<String>())
+    } else {
+        collapsed
+    }
+}
+
+// The URL to scrape for what was typed: trimmed, with https:// added when no
+// scheme is given. Errors describe input that can't be a web page address.
+fn normalize_scrape_url(input: &str) -> Result<String, String> {
+    let input = input.trim();
+    if input.contains(char::is_whitespace) {
+        return Err("URL can't contain spaces".to_string());
+    }
+    let with_scheme = if input.contains("://") { input.to_string() } else { format!("https://{}", input) };
+    let parsed = url::Url::parse(&with_scheme).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Only http:// and https:// URLs can be scraped, not {}://", parsed.scheme()));
+    }
+    // A bare word like "example" is a typo, not a host; IP addresses and localhost are fine
+    match parsed.host() {
+        Some(url::Host::Domain(domain)) if !domain.contains('.') && domain != "localhost" => {
+            Err(format!("\"{}\" isn't a full domain name (e.g. {}.com)", domain, domain))
+        }
+        Some(_) => Ok(with_scheme),
+        None => Err("URL has no host".to_string()),
+    }
+}
+
+// Cheap sanity check used before auto-scraping a paste: an http(s) URL with a
+// host and no whitespace, so partial or accidental pastes don't fire requests.
+fn looks_like_url(text: &str) -> bool {
+    let text = text.trim();
+    let rest = text.strip_prefix("https://").or_else(|| text.strip_prefix("http://"));
+    rest.is_some_and(|rest| {
+        let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+        !text.contains(char::is_whitespace) && (host.contains('.') || host.starts_with("localhost"))
+    })
+}
+
+// The leading part of `content` that fits in `max_bytes`, cut at a line break
+// when there's one reasonably close, and whether anything was cut off.
+fn truncate_for_display(content: &str, max_bytes: usize) -> (&str, bool) {
+    if content.len() <= max_bytes {
+        return (content, false);
+    }
+    let mut cut = max_bytes;
+    while !content.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    if let Some(newline) = content[..cut].rfind('\n').filter(|&i| i >= cut / 2) {
+        cut = newline;
+    }
+    (&content[..cut], true)
+}
+
+// Host part of a URL for display, e.g. `example.com` for
+// `https://user@example.com/a?b`. Falls back to the whole string for
+// anything that doesn't look like a URL with a host.
+fn url_host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    if host.is_empty() { url } else { host }
+}
+
+// File name for an export of `url`: its host with anything unsafe in a file
+// name replaced, e.g. `example.com_8080.md` for the extension "md".
+fn export_filename(url: &str, extension: &str) -> String {
+    let host: String = url_host(url)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    let host = host.trim_matches('.');
+    if host.is_empty() {
+        format!("scraped_content.{}", extension)
+    } else {
+        format!("{}.{}", host, extension)
+    }
+}
+
+// Human-readable byte count, e.g. "512 B", "42 KB", "1.3 MB".
+fn format_size(bytes: usize) -> String {
+    const KB: f64 = 1024.0;
+    let bytes_f = bytes as f64;
+    if bytes_f < KB {
+        format!("{} B", bytes)
+    } else if bytes_f < KB * KB {
+        format!("{:.0} KB", bytes_f / KB)
+    } else {
+        format!("{:.1} MB", bytes_f / (KB * KB))
+    }
+}
+
+// Count with thousands separators, e.g. "1,230".
+fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out
+}
+
+// Small outlined label used for the result's format badges.
+fn format_badge(ui: &mut egui::Ui, text: &str) -> egui::Response {
+    egui::Frame::new()
+        .stroke(ui.visuals().widgets.noninteractive.bg_stroke)
+        .corner_radius(4.0)
+        .inner_margin(egui::Margin::symmetric(4, 1))
+        .show(ui, |ui| ui.small(text))
+        .response
+}
+
+/// A shell command reproducing `request` with curl, for sharing and debugging.
+fn curl_command(request: &ehttp::Request) -> String {
+    let mut command = format!("curl -X {} {}", request.method, shell_quote(&request.url));
+    for (name, value) in &request.headers.headers {
+        command += &format!(" \\\n  -H {}", shell_quote(&format!("{}: {}", name, value)));
+    }
+    if !request.body.is_empty() {
+        command += &format!(" \\\n  --data {}", shell_quote(&String::from_utf8_lossy(&request.body)));
+    }
+    command
+}
+
+// Single-quotes `text` for POSIX shells
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+// Fires a history request (delete/restore) whose result only needs logging.
+fn send_history_request(request: ehttp::Request, action: &'static str) {
+    ehttp::fetch(request, move |result| match result {
+        Ok(response) if response.ok => log::info!("History {} succeeded", action),
+        Ok(response) => log::error!("History {} failed: HTTP {} {}", action, response.status, response.status_text),
+        Err(e) => log::error!("History {} failed: {}", action, e),
+    });
+}
+
+// Leading `max_chars` characters of a result, for quick previews. Only scans as
+// far as the cut point so hovering over long documents stays cheap.
+fn preview_text(markdown: &str, max_chars: usize) -> String {
+    match markdown.char_indices().nth(max_chars) {
+        Some((cut, _)) => format!("{}…", markdown[..cut].trim_end()),
+        None => markdown.to_string(),
+    }
+}
+
+// Runs the in-process Local scraper off the UI thread.
+fn spawn_local_scrape(url: String) -> Promise<Result<String, ProcessingError>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Promise::spawn_thread("local_scrape", move || {
+            futures::executor::block_on(processing::fetch_and_convert(url))
         })
     }
+    #[cfg(target_arch = "wasm32")]
+    {
+        Promise::spawn_async(processing::fetch_and_convert(url))
+    }
 }
 
 
 // ---- Helper Functions for Saving Files ----
 // (These remain outside the impl eframe::App block)
 
-fn save_markdown_file(filename: &str, content: &str) {
+// Saves Markdown for export, hard-wrapped when an export wrap width is set.
+fn export_markdown(filename: &str, content: &str, wrap_width: Option<usize>) {
+    match wrap_width {
+        Some(width) => save_text_file(filename, &ruscraper_markdown::wrap(content, width), "md"),
+        None => save_text_file(filename, content, "md"),
+    }
+}
+
+// Saves a text export; `extension` ("md", "html" or "json") picks the file
+// type offered by the native save dialog.
+fn save_text_file(filename: &str, content: &str, extension: &str) {
     #[cfg(not(target_arch = "wasm32"))]
     {
+        let kind = match extension {
+            "html" => "HTML",
+            "json" => "JSON",
+            _ => "Markdown",
+        };
         let Some(path) = rfd::FileDialog::new()
             .set_file_name(filename)
-            .add_filter("Markdown", &["md"])
+            .add_filter(kind, &[extension])
             .save_file() else {
             log::info!("User cancelled save dialog.");
             return;
         };
         match std::fs::write(&path, content) {
-            Ok(_) => log::info!("Markdown saved to: {:?}", path),
-            Err(e) => log::error!("Failed to save markdown file: {}", e),
+            Ok(_) => log::info!("{} saved to: {:?}", kind, path),
+            Err(e) => log::error!("Failed to save {} file: {}", kind, e),
         }
     }
     #[cfg(target_arch = "wasm32")]
     {
-        trigger_download(filename, content);
+        let _ = extension; // The MIME type follows the file name
+        trigger_download(filename, content.as_bytes());
     }
 }
 
-fn save_pdf_file(filename: &str, content: &str) {
+fn save_pdf_file(filename: &str, content: &str, style: ExportStyle) {
     #[cfg(not(target_arch = "wasm32"))]
     {
          let Some(path) = rfd::FileDialog::new()
@@ -628,7 +2584,7 @@ fn save_pdf_file(filename: &str, content: &str) {
             log::info!("User cancelled save dialog.");
             return;
         };
-        match create_basic_pdf(content) {
+        match pdf::markdown_to_pdf(content, style) {
             Ok(pdf_bytes) => {
                 match std::fs::write(&path, pdf_bytes) {
                     Ok(_) => log::info!("PDF saved to: {:?}", path),
@@ -636,42 +2592,21 @@ fn save_pdf_file(filename: &str, content: &str) {
                 }
             }
             Err(e) => {
-                 log::error!("Failed to generate basic PDF: {}", e);
+                 log::error!("Failed to generate PDF: {}", e);
             }
         }
     }
     #[cfg(target_arch = "wasm32")]
     {
-        log::warn!("WASM PDF export is basic: downloading raw text with .pdf extension.");
-        trigger_download(filename, content);
-    }
-}
-
-#[cfg(not(target_arch = "wasm32"))]
-fn create_basic_pdf(content: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    let (doc, page1, layer1) = PdfDocument::new("Scraped Content", Mm(210.0), Mm(297.0), "Layer 1");
-    let current_layer = doc.get_page(page1).get_layer(layer1);
-    let font = doc.add_builtin_font(printpdf::BuiltinFont::Helvetica)?;
-    let font_size = 10.0;
-    let line_height = 12.0;
-    let margin_top = 280.0;
-    let margin_bottom = 15.0;
-    let mut y_position = margin_top;
-    current_layer.set_font(&font, font_size);
-    for line in content.lines() {
-        if y_position < margin_bottom {
-            log::warn!("PDF content truncated due to reaching page bottom.");
-            break;
+        match pdf::markdown_to_pdf(content, style) {
+            Ok(pdf_bytes) => trigger_download(filename, &pdf_bytes),
+            Err(e) => log::error!("Failed to generate PDF: {}", e),
         }
-        current_layer.use_text(line.to_string(), font_size, Mm(10.0), Mm(y_position), &font);
-        y_position -= line_height;
     }
-    let pdf_bytes = doc.save_to_bytes()?;
-    Ok(pdf_bytes)
 }
 
 #[cfg(target_arch = "wasm32")]
-fn trigger_download(filename: &str, content: &str) {
+fn trigger_download(filename: &str, content: &[u8]) {
     use base64::{engine::general_purpose, Engine as _};
 
     let window = web_sys::window().expect("no global `window` exists");
@@ -685,8 +2620,17 @@ fn trigger_download(filename: &str, content: &str) {
         .expect("Failed to cast to HtmlAnchorElement");
 
     let base64_content = general_purpose::STANDARD.encode(content);
-    let mime_type = if filename.ends_with(".pdf") { "text/plain" } else { "text/markdown" };
-    let href = format!("data:{};charset=utf-8;base64,{}", mime_type, base64_content);
+    // PDFs are binary; the text formats are UTF-8
+    let mime_type = if filename.ends_with(".pdf") {
+        "application/pdf"
+    } else if filename.ends_with(".html") {
+        "text/html;charset=utf-8"
+    } else if filename.ends_with(".json") {
+        "application/json"
+    } else {
+        "text/markdown;charset=utf-8"
+    };
+    let href = format!("data:{};base64,{}", mime_type, base64_content);
 
     link.set_href(&href);
     link.set_download(filename);
@@ -698,3 +2642,40 @@ fn trigger_download(filename: &str, content: &str) {
     body.remove_child(&link).expect("Failed to remove link");
     log::info!("Triggered download for {}", filename);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_scrape_url_adds_https_when_no_scheme_is_given() {
+        assert_eq!(normalize_scrape_url("example.com/docs").unwrap(), "https://example.com/docs");
+        assert_eq!(normalize_scrape_url("http://example.com").unwrap(), "http://example.com");
+    }
+
+    #[test]
+    fn normalize_scrape_url_accepts_localhost_and_ip_addresses() {
+        assert_eq!(normalize_scrape_url("localhost:8080").unwrap(), "https://localhost:8080");
+        assert_eq!(normalize_scrape_url("http://127.0.0.1:3000/").unwrap(), "http://127.0.0.1:3000/");
+        assert_eq!(normalize_scrape_url("[::1]").unwrap(), "https://[::1]");
+    }
+
+    #[test]
+    fn normalize_scrape_url_rejects_bare_words() {
+        let err = normalize_scrape_url("example").unwrap_err();
+        assert!(err.contains("isn't a full domain name"), "{}", err);
+    }
+
+    #[test]
+    fn normalize_scrape_url_rejects_non_http_schemes() {
+        let err = normalize_scrape_url("ftp://example.com/file").unwrap_err();
+        assert!(err.contains("not ftp://"), "{}", err);
+    }
+
+    #[test]
+    fn normalize_scrape_url_trims_but_rejects_inner_whitespace() {
+        assert_eq!(normalize_scrape_url("  example.com \n").unwrap(), "https://example.com");
+        assert!(normalize_scrape_url("example.com/a page").is_err());
+        assert!(normalize_scrape_url("   ").is_err());
+    }
+}
@@ -0,0 +1,39 @@
+use similar::{ChangeTag, TextDiff};
+
+/// Lines of unchanged context shown around each change.
+const CONTEXT_LINES: usize = 3;
+
+/// Renders a colored unified line diff of `old` -> `new`: deletions in red,
+/// additions in green, unchanged context in the default text color.
+pub fn show_diff(ui: &mut egui::Ui, old: &str, new: &str) {
+    let diff = TextDiff::from_lines(old, new);
+    let groups = diff.grouped_ops(CONTEXT_LINES);
+    if groups.is_empty() {
+        ui.label("The two items have identical content.");
+        return;
+    }
+
+    let added = egui::Color32::from_rgb(0x2e, 0xa0, 0x43);
+    let removed = egui::Color32::from_rgb(0xcf, 0x22, 0x2e);
+
+    for (i, group) in groups.iter().enumerate() {
+        if i > 0 {
+            ui.separator();
+        }
+        for op in group {
+            for change in diff.iter_changes(op) {
+                let (sign, color) = match change.tag() {
+                    ChangeTag::Delete => ("-", Some(removed)),
+                    ChangeTag::Insert => ("+", Some(added)),
+                    ChangeTag::Equal => (" ", None),
+                };
+                let line = format!("{}{}", sign, change.value().trim_end_matches('\n'));
+                let mut text = egui::RichText::new(line).monospace();
+                if let Some(color) = color {
+                    text = text.color(color);
+                }
+                ui.label(text);
+            }
+        }
+    }
+}
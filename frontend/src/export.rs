@@ -0,0 +1,47 @@
+use pulldown_cmark::{html, Options, Parser};
+
+/// How a result is laid out on screen, so exports can reproduce it.
+#[derive(Clone, Copy, Debug)]
+pub struct ExportStyle {
+    /// Body text size in points, after the user's font scale.
+    pub font_size: f32,
+    /// Maximum content width in points; None fills the available width.
+    pub content_width: Option<f32>,
+}
+
+/// Renders `markdown` as a standalone HTML page sized like the on-screen view.
+pub fn markdown_to_html(title: &str, markdown: &str, style: ExportStyle) -> String {
+    let parser = Parser::new_ext(markdown, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH);
+    let mut body = String::new();
+    html::push_html(&mut body, parser);
+
+    let max_width = style
+        .content_width
+        .map_or_else(|| "none".to_string(), |width| format!("{:.0}px", width));
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; font-size: {font_size:.1}px; line-height: 1.4; max-width: {max_width}; margin: 1em auto; padding: 0 1em; }}
+pre, code {{ font-size: 0.9em; }}
+pre {{ overflow-x: auto; }}
+table {{ border-collapse: collapse; }}
+th, td {{ border: 1px solid #ccc; padding: 0.2em 0.5em; }}
+img {{ max-width: 100%; }}
+</style>
+</head>
+<body>
+{body}</body>
+</html>
+"#,
+        title = escape_html(title),
+        font_size = style.font_size,
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
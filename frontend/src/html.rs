@@ -0,0 +1,55 @@
+// HTML to Markdown for the Local scraper: the whole page body, with links and
+// images made absolute against the page URL.
+
+use ruscraper_markdown::html::Converter;
+use scraper::{Html, Selector};
+use url::Url;
+
+/// Converts an HTML document to Markdown. Relative link and image URLs are
+/// resolved against the page's `<base href>` or else `page_url`. The page
+/// `<title>` becomes the top heading when the body doesn't start with one.
+pub fn to_markdown(html: &str, page_url: &Url) -> String {
+    let document = Html::parse_document(html);
+    let base_href = Selector::parse("base[href]").expect("valid selector");
+    let base = document
+        .select(&base_href)
+        .next()
+        .and_then(|base| page_url.join(base.value().attr("href")?).ok())
+        .unwrap_or_else(|| page_url.clone());
+    let body = Converter::default().resolving_against(&base).block_markdown(document.root_element());
+
+    let title = Selector::parse("title").expect("valid selector");
+    let title = document.select(&title).next().map(|title| collapse_whitespace(&title.text().collect::<String>()));
+    match title.filter(|title| !title.is_empty()) {
+        Some(title) if !body.starts_with("# ") => format!("# {}\n\n{}", title, body),
+        _ => body,
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(html: &str) -> String {
+        to_markdown(html, &Url::parse("https://example.com/docs/page").unwrap())
+    }
+
+    #[test]
+    fn relative_links_are_resolved_against_the_page_url() {
+        let html = r##"<p><a href="/about">About</a> <a href="next">Next</a> <a href="#top">Top</a></p>"##;
+        assert_eq!(
+            convert(html),
+            "[About](https://example.com/about) [Next](https://example.com/docs/next) [Top](#top)"
+        );
+    }
+
+    #[test]
+    fn base_href_takes_precedence_over_the_page_url() {
+        let html = r#"<head><base href="https://cdn.example.org/assets/"></head><body><img src="logo.png" alt="Logo"></body>"#;
+        assert_eq!(convert(html), "![Logo](https://cdn.example.org/assets/logo.png)");
+    }
+}
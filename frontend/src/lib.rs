@@ -1,5 +1,11 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod app;
+mod diff_view;
+mod export;
+mod html;
+mod pdf;
 mod processing;
+mod sanitize;
+mod tables;
 pub use app::TemplateApp;
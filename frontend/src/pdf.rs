@@ -0,0 +1,347 @@
+use printpdf::{
+    BuiltinFont, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Point,
+};
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+use crate::export::ExportStyle;
+
+const PAGE_WIDTH_MM: f32 = 210.0; // A4
+const PAGE_HEIGHT_MM: f32 = 297.0;
+const MARGIN_MM: f32 = 15.0;
+const PT_PER_MM: f32 = 72.0 / 25.4;
+const LINE_SPACING: f32 = 1.4;
+const LIST_INDENT_MM: f32 = 6.0;
+
+// Advance widths in 1/1000 em for ASCII 32..=126, from the standard AFM files
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, // space../
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, // 0..?
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, // @..O
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556, // P.._
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556, // `..o
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584, // p..~
+];
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, // space../
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, // 0..?
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778, // @..O
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556, // P.._
+    333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611, // `..o
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584, // p..~
+];
+const COURIER_WIDTH: u16 = 600;
+const BULLET_WIDTH: u16 = 350;
+const OTHER_WIDTH: u16 = 556; // Anything else, roughly an average glyph
+
+#[derive(Clone, Copy, PartialEq)]
+enum Face {
+    Regular,
+    Bold,
+    Mono,
+}
+
+impl Face {
+    fn char_width(self, c: char) -> u16 {
+        let table = match self {
+            Face::Regular => &HELVETICA_WIDTHS,
+            Face::Bold => &HELVETICA_BOLD_WIDTHS,
+            Face::Mono => return COURIER_WIDTH,
+        };
+        match c {
+            ' '..='~' => table[c as usize - 32],
+            '•' => BULLET_WIDTH,
+            _ => OTHER_WIDTH,
+        }
+    }
+
+    // Width of `text` in mm at `size` points
+    fn text_width(self, text: &str, size: f32) -> f32 {
+        let units: u32 = text.chars().map(|c| self.char_width(c) as u32).sum();
+        units as f32 / 1000.0 * size / PT_PER_MM
+    }
+}
+
+enum Kind {
+    Heading(HeadingLevel),
+    Paragraph,
+    Item { depth: usize, marker: String },
+    Code,
+    Rule,
+}
+
+struct Block {
+    kind: Kind,
+    text: String,
+}
+
+/// Renders `markdown` as an A4 PDF: text is wrapped to the page width using
+/// the Helvetica metrics and continues on new pages as needed. Headings are
+/// set larger and bold, list items get bullets or numbers, and code blocks
+/// use Courier; images and raw HTML are left out.
+pub fn markdown_to_pdf(markdown: &str, style: ExportStyle) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let (doc, page, layer) = PdfDocument::new("Scraped Content", Mm(PAGE_WIDTH_MM), Mm(PAGE_HEIGHT_MM), "Layer 1");
+    let mut writer = Writer {
+        layer: doc.get_page(page).get_layer(layer),
+        regular: doc.add_builtin_font(BuiltinFont::Helvetica)?,
+        bold: doc.add_builtin_font(BuiltinFont::HelveticaBold)?,
+        mono: doc.add_builtin_font(BuiltinFont::Courier)?,
+        doc,
+        pages: 1,
+        y: PAGE_HEIGHT_MM - MARGIN_MM,
+    };
+
+    // egui points are CSS pixels; PDF sizes are in 1/72 inch
+    let body_size = style.font_size * 0.75;
+    let page_text_width = PAGE_WIDTH_MM - 2.0 * MARGIN_MM;
+    let text_width = style
+        .content_width
+        .map_or(page_text_width, |width| (width * 0.75 / PT_PER_MM).min(page_text_width));
+
+    for block in blocks(markdown) {
+        match block.kind {
+            Kind::Heading(level) => {
+                let scale = match level {
+                    HeadingLevel::H1 => 2.0,
+                    HeadingLevel::H2 => 1.6,
+                    HeadingLevel::H3 => 1.3,
+                    _ => 1.1,
+                };
+                let size = body_size * scale;
+                writer.space(size * 0.6);
+                writer.text(&block.text, Face::Bold, size, MARGIN_MM, text_width);
+                writer.space(size * 0.2);
+            }
+            Kind::Paragraph => {
+                writer.text(&block.text, Face::Regular, body_size, MARGIN_MM, text_width);
+                writer.space(body_size * 0.5);
+            }
+            Kind::Item { depth, marker } => {
+                let marker_x = MARGIN_MM + depth as f32 * LIST_INDENT_MM;
+                let text_x = marker_x + LIST_INDENT_MM;
+                writer.marker(&marker, body_size, marker_x);
+                writer.text(&block.text, Face::Regular, body_size, text_x, text_width - (text_x - MARGIN_MM));
+                writer.space(body_size * 0.2);
+            }
+            Kind::Code => {
+                let size = body_size * 0.9;
+                writer.text(block.text.trim_end_matches('\n'), Face::Mono, size, MARGIN_MM, text_width);
+                writer.space(body_size * 0.5);
+            }
+            Kind::Rule => {
+                writer.space(body_size * 0.5);
+                writer.rule(MARGIN_MM, MARGIN_MM + text_width);
+                writer.space(body_size * 0.5);
+            }
+        }
+    }
+
+    log::info!("PDF laid out on {} page(s)", writer.pages);
+    Ok(writer.doc.save_to_bytes()?)
+}
+
+struct Writer {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    mono: IndirectFontRef,
+    pages: usize,
+    y: f32, // Top of the next line, in mm from the page bottom
+}
+
+impl Writer {
+    fn font(&self, face: Face) -> &IndirectFontRef {
+        match face {
+            Face::Regular => &self.regular,
+            Face::Bold => &self.bold,
+            Face::Mono => &self.mono,
+        }
+    }
+
+    fn new_page(&mut self) {
+        self.pages += 1;
+        let (page, layer) = self.doc.add_page(
+            Mm(PAGE_WIDTH_MM),
+            Mm(PAGE_HEIGHT_MM),
+            format!("Layer {}", self.pages),
+        );
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.y = PAGE_HEIGHT_MM - MARGIN_MM;
+    }
+
+    // Moves down by `height` mm, starting a new page if that leaves the printable area
+    fn advance(&mut self, height: f32) {
+        if self.y - height < MARGIN_MM {
+            self.new_page();
+        }
+        self.y -= height;
+    }
+
+    // Vertical gap of `size` points; dropped at the top of a page
+    fn space(&mut self, size: f32) {
+        if self.y < PAGE_HEIGHT_MM - MARGIN_MM {
+            self.y = (self.y - size / PT_PER_MM).max(MARGIN_MM);
+        }
+    }
+
+    // Writes `text` wrapped to `width` mm, keeping its hard line breaks
+    fn text(&mut self, text: &str, face: Face, size: f32, x: f32, width: f32) {
+        let line_height = size * LINE_SPACING / PT_PER_MM;
+        for line in text.lines().flat_map(|line| wrap(line, face, size, width)) {
+            self.advance(line_height);
+            // The baseline sits about a fifth of the line height above its bottom
+            let baseline = self.y + line_height * 0.2;
+            self.layer.use_text(line, size, Mm(x), Mm(baseline), self.font(face));
+        }
+    }
+
+    // List marker on the line the item's text starts on
+    fn marker(&mut self, marker: &str, size: f32, x: f32) {
+        let line_height = size * LINE_SPACING / PT_PER_MM;
+        if self.y - line_height < MARGIN_MM {
+            self.new_page();
+        }
+        let baseline = self.y - line_height * 0.8;
+        self.layer.use_text(marker, size, Mm(x), Mm(baseline), &self.regular);
+    }
+
+    fn rule(&mut self, from: f32, to: f32) {
+        self.layer.set_outline_thickness(0.5);
+        self.layer.add_line(Line {
+            points: vec![(Point::new(Mm(from), Mm(self.y)), false), (Point::new(Mm(to), Mm(self.y)), false)],
+            is_closed: false,
+        });
+    }
+}
+
+// Breaks `line` at spaces so each piece fits in `width` mm; words longer than
+// a whole line are split wherever they overflow
+fn wrap(line: &str, face: Face, size: f32, width: f32) -> Vec<String> {
+    let space = face.text_width(" ", size);
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut current_width = 0.0;
+    for word in line.split(' ') {
+        let word_width = face.text_width(word, size);
+        if !current.is_empty() && current_width + space + word_width > width {
+            lines.push(std::mem::take(&mut current));
+            current_width = 0.0;
+        }
+        if !current.is_empty() {
+            current.push(' ');
+            current_width += space;
+        }
+        for c in word.chars() {
+            let char_width = face.text_width(c.encode_utf8(&mut [0; 4]), size);
+            if !current.is_empty() && current_width + char_width > width {
+                lines.push(std::mem::take(&mut current));
+                current_width = 0.0;
+            }
+            current.push(c);
+            current_width += char_width;
+        }
+    }
+    lines.push(current);
+    lines
+}
+
+// Flattens the Markdown into the blocks the PDF lays out, with inline markup
+// reduced to its text
+fn blocks(markdown: &str) -> Vec<Block> {
+    let parser = Parser::new_ext(markdown, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH);
+    let mut blocks = Vec::new();
+    let mut current: Option<Block> = None;
+    let mut lists: Vec<Option<u64>> = Vec::new(); // Next number of each open list; None when unordered
+    let mut image_depth = 0;
+
+    fn flush(blocks: &mut Vec<Block>, current: &mut Option<Block>) {
+        if let Some(block) = current.take() {
+            if !block.text.trim().is_empty() {
+                blocks.push(block);
+            }
+        }
+    }
+
+    for event in parser {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                flush(&mut blocks, &mut current);
+                current = Some(Block { kind: Kind::Heading(level), text: String::new() });
+            }
+            Event::Start(Tag::Paragraph) | Event::Start(Tag::TableHead) | Event::Start(Tag::TableRow) => {
+                if current.is_none() {
+                    current = Some(Block { kind: Kind::Paragraph, text: String::new() });
+                } else if let Some(block) = current.as_mut() {
+                    // A further paragraph of the same list item
+                    if !block.text.is_empty() {
+                        block.text.push('\n');
+                    }
+                }
+            }
+            Event::Start(Tag::CodeBlock(_)) => {
+                flush(&mut blocks, &mut current);
+                current = Some(Block { kind: Kind::Code, text: String::new() });
+            }
+            Event::Start(Tag::List(first)) => {
+                // The parent item's own text comes before the nested list
+                flush(&mut blocks, &mut current);
+                lists.push(first);
+            }
+            Event::Start(Tag::Item) => {
+                flush(&mut blocks, &mut current);
+                let depth = lists.len().saturating_sub(1);
+                let marker = match lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}.", *number - 1)
+                    }
+                    _ => "•".to_string(),
+                };
+                current = Some(Block { kind: Kind::Item { depth, marker }, text: String::new() });
+            }
+            Event::Start(Tag::Image { .. }) => image_depth += 1,
+            Event::End(TagEnd::Image) => image_depth -= 1,
+            Event::End(TagEnd::Heading(_))
+            | Event::End(TagEnd::CodeBlock)
+            | Event::End(TagEnd::TableHead)
+            | Event::End(TagEnd::TableRow) => flush(&mut blocks, &mut current),
+            Event::End(TagEnd::Paragraph) => {
+                if matches!(current, Some(Block { kind: Kind::Paragraph, .. })) {
+                    flush(&mut blocks, &mut current);
+                }
+            }
+            Event::End(TagEnd::Item) => flush(&mut blocks, &mut current),
+            Event::End(TagEnd::List(_)) => {
+                lists.pop();
+            }
+            Event::End(TagEnd::TableCell) => {
+                if let Some(block) = current.as_mut() {
+                    block.text.push_str("   ");
+                }
+            }
+            Event::Rule => {
+                flush(&mut blocks, &mut current);
+                blocks.push(Block { kind: Kind::Rule, text: String::new() });
+            }
+            Event::Text(text) | Event::Code(text) if image_depth == 0 => {
+                current
+                    .get_or_insert_with(|| Block { kind: Kind::Paragraph, text: String::new() })
+                    .text
+                    .push_str(&text);
+            }
+            Event::SoftBreak => {
+                if let Some(block) = current.as_mut() {
+                    block.text.push(' ');
+                }
+            }
+            Event::HardBreak => {
+                if let Some(block) = current.as_mut() {
+                    block.text.push('\n');
+                }
+            }
+            _ => {}
+        }
+    }
+    flush(&mut blocks, &mut current);
+    blocks
+}
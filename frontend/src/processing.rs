@@ -1,64 +1,148 @@
-#[cfg(target_arch = "wasm32")]
-use gloo_timers::future::TimeoutFuture;
-
-#[cfg(not(target_arch = "wasm32"))]
-use std::{thread, time::Duration};
+use std::collections::HashMap;
 
 /// Represents errors that can occur during the fetching or conversion process.
 #[derive(Debug, thiserror::Error)]
 pub enum ProcessingError {
     #[error("Network request failed: {0}")]
-    FetchError(String), // In a real app, this might be reqwest::Error or similar
+    FetchError(String),
     #[error("Failed to convert content: {0}")]
     ConversionError(String),
-    #[error("Mock error: {0}")]
-    MockError(String),
 }
 
-/// Simulates fetching content from a URL and converting it to Markdown.
-///
-/// In a real application, this would involve:
-/// 1. Making an HTTP GET request to the `url`.
-/// 2. Parsing the HTML response.
-/// 3. Converting the HTML to Markdown.
-///
-/// This mock version just simulates a delay and returns predefined content or an error.
+/// Fetches `url` and converts the page to Markdown, without any backend:
+/// 1. GETs the page, failing on network errors and non-2xx statuses.
+/// 2. Decodes the body per its `Content-Encoding`.
+/// 3. Converts HTML to Markdown; plain text and Markdown pass through as is.
 pub async fn fetch_and_convert(url: String) -> Result<String, ProcessingError> {
     log::info!("Processing request for URL: {}", url);
+    let page_url = url::Url::parse(url.trim()).map_err(|e| ProcessingError::FetchError(format!("Invalid URL: {}", e)))?;
 
-    // Simulate network delay
-    #[cfg(target_arch = "wasm32")]
-    {
-        TimeoutFuture::new(1_500).await; // Simulate 1.5 seconds loading time
+    let mut request = ehttp::Request::get(page_url.as_str());
+    request.headers.insert("Accept", "text/html,application/xhtml+xml,text/plain;q=0.9,*/*;q=0.5");
+    let response = ehttp::fetch_async(request).await.map_err(|e| {
+        // Browsers report a blocked cross-origin request as a bare network error
+        if cfg!(target_arch = "wasm32") {
+            ProcessingError::FetchError(format!("{} (the site may not allow cross-origin requests from the web app)", e))
+        } else {
+            ProcessingError::FetchError(e)
+        }
+    })?;
+    if !response.ok {
+        return Err(ProcessingError::FetchError(format!(
+            "{} returned HTTP {} {}",
+            page_url, response.status, response.status_text
+        )));
     }
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        thread::sleep(Duration::from_millis(1500));
+
+    let content_type = response.content_type().unwrap_or("text/html").to_ascii_lowercase();
+    let body = decode_body(response.bytes, response.headers.get("content-encoding"))?;
+    let body = String::from_utf8_lossy(&body);
+    let markdown = if content_type.contains("html") {
+        crate::html::to_markdown(&body, &page_url)
+    } else if content_type.starts_with("text/") {
+        body.into_owned()
+    } else {
+        return Err(ProcessingError::ConversionError(format!("Not a web page: {}", content_type)));
+    };
+    if markdown.trim().is_empty() {
+        return Err(ProcessingError::ConversionError("The page has no text content".to_string()));
     }
+    log::info!("Converted {} to {} bytes of Markdown", page_url, markdown.len());
+    Ok(markdown)
+}
 
-    // --- Mock Logic ---
-    // Simulate potential errors based on URL or randomly
-    if url.contains("error") {
-        log::warn!("Simulating a mock error for URL: {}", url);
-        Err(ProcessingError::MockError("Simulated failure to process URL.".to_string()))
-    } else if url.trim().is_empty() {
-         Err(ProcessingError::MockError("URL cannot be empty.".to_string()))
+/// Decodes a response body according to its `Content-Encoding` header
+/// (`gzip`, `deflate`, `br`, or a comma-separated chain of them) so the HTML
+/// parser never sees compressed bytes.
+///
+/// HTTP clients that decompress transparently may leave the header in place;
+/// a body that fails to decode but is already valid UTF-8 is passed through.
+pub fn decode_body(bytes: Vec<u8>, content_encoding: Option<&str>) -> Result<Vec<u8>, ProcessingError> {
+    let Some(content_encoding) = content_encoding else {
+        return Ok(bytes);
+    };
+    // Encodings are listed in the order they were applied, so undo them in reverse
+    let mut body = bytes;
+    for encoding in content_encoding.split(',').map(|e| e.trim().to_ascii_lowercase()).rev() {
+        body = match decode_one(&body, &encoding) {
+            Ok(decoded) => decoded,
+            Err(_) if std::str::from_utf8(&body).is_ok() => {
+                log::debug!("Body is already decoded despite Content-Encoding: {}", encoding);
+                body
+            }
+            Err(e) => return Err(e),
+        };
     }
-     else {
-        log::info!("Successfully processed URL: {}", url);
-        // Return mock markdown content
-        Ok(format!(
-            "# Mock Result for: `{}`
+    Ok(body)
+}
+
+fn decode_one(body: &[u8], encoding: &str) -> Result<Vec<u8>, ProcessingError> {
+    use std::io::Read;
+
+    let mut decoded = Vec::new();
+    let result = match encoding {
+        "" | "identity" => return Ok(body.to_vec()),
+        "gzip" | "x-gzip" => flate2::read::MultiGzDecoder::new(body).read_to_end(&mut decoded),
+        // "deflate" is meant to be zlib-wrapped, but some servers send raw deflate
+        "deflate" => flate2::read::ZlibDecoder::new(body).read_to_end(&mut decoded).or_else(|_| {
+            decoded.clear();
+            flate2::read::DeflateDecoder::new(body).read_to_end(&mut decoded)
+        }),
+        "br" => brotli_decompressor::Decompressor::new(body, 4096).read_to_end(&mut decoded),
+        other => {
+            return Err(ProcessingError::ConversionError(format!(
+                "Unsupported Content-Encoding: {}",
+                other
+            )))
+        }
+    };
+    result.map(|_| decoded).map_err(|e| {
+        ProcessingError::ConversionError(format!("Failed to decode {}-encoded response body: {}", encoding, e))
+    })
+}
 
-This is simulated Markdown content.
+/// A locally scraped page kept in the [`LocalCache`].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct CachedPage {
+    markdown: String,
+    fetched_at: i64, // Unix timestamp (seconds)
+}
 
-- Fetched data would go here.
-- Conversion logic would be applied.
+/// Client-side cache of locally scraped results, keyed by normalized URL.
+///
+/// Mirrors the backend's URL cache for the in-process scraper so repeated
+/// local scrapes of the same page return instantly. Persisted with the app state.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct LocalCache {
+    entries: HashMap<String, CachedPage>,
+}
 
-*Timestamp:* `{:?}`",
-            url,
-            chrono::Utc::now() // Add a timestamp to show it's dynamic
-        ))
+impl LocalCache {
+    /// Returns the cached Markdown for `url` if it is younger than `ttl_secs`.
+    pub fn get(&self, url: &str, ttl_secs: u64) -> Option<&str> {
+        let now = chrono::Utc::now().timestamp();
+        self.entries
+            .get(&cache_key(url))
+            .filter(|page| now.saturating_sub(page.fetched_at) < ttl_secs as i64)
+            .map(|page| page.markdown.as_str())
     }
-    // --- End Mock Logic ---
-} 
\ No newline at end of file
+
+    /// Stores a freshly scraped page, dropping entries older than `ttl_secs`.
+    pub fn insert(&mut self, url: &str, markdown: String, ttl_secs: u64) {
+        let now = chrono::Utc::now().timestamp();
+        self.entries
+            .retain(|_, page| now.saturating_sub(page.fetched_at) < ttl_secs as i64);
+        self.entries.insert(
+            cache_key(url),
+            CachedPage {
+                markdown,
+                fetched_at: now,
+            },
+        );
+    }
+}
+
+// Treats `https://a.com/x/` and ` https://a.com/x` as the same page.
+fn cache_key(url: &str) -> String {
+    url.trim().trim_end_matches('/').to_string()
+}
@@ -0,0 +1,170 @@
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// Which Markdown elements the viewer renders. Disallowed ones are removed
+/// from the source before it reaches the viewer.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ViewerFilter {
+    pub images: bool,
+    /// When off, links are shown as their plain text.
+    pub links: bool,
+    pub raw_html: bool,
+}
+
+impl Default for ViewerFilter {
+    fn default() -> Self {
+        Self { images: true, links: true, raw_html: true }
+    }
+}
+
+impl ViewerFilter {
+    /// Text only: no images, links or HTML.
+    pub const SAFE: Self = Self { images: false, links: false, raw_html: false };
+
+    pub fn allows_everything(&self) -> bool {
+        self.images && self.links && self.raw_html
+    }
+}
+
+/// Removes the elements `filter` disallows from `markdown`. Everything else,
+/// including the formatting of what's kept, is left as written.
+pub fn filter_markdown<'a>(markdown: &'a str, filter: ViewerFilter) -> Cow<'a, str> {
+    if filter.allows_everything() {
+        return Cow::Borrowed(markdown);
+    }
+
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS | Options::ENABLE_FOOTNOTES;
+    // Source ranges to replace, never overlapping
+    let mut edits: Vec<(Range<usize>, &str)> = Vec::new();
+    // Depth inside an element that's being removed whole
+    let mut skip_depth = 0;
+    // Open links being unwrapped: the link's range, then the span of its text
+    let mut links: Vec<(Range<usize>, Option<Range<usize>>)> = Vec::new();
+
+    for (event, range) in Parser::new_ext(markdown, options).into_offset_iter() {
+        if skip_depth > 0 {
+            match event {
+                Event::Start(_) => skip_depth += 1,
+                Event::End(_) => skip_depth -= 1,
+                _ => {}
+            }
+            continue;
+        }
+        match event {
+            Event::Start(Tag::Image { .. }) if !filter.images => {
+                edits.push((range, ""));
+                skip_depth = 1;
+                continue;
+            }
+            Event::Start(Tag::HtmlBlock) if !filter.raw_html => {
+                edits.push((range, ""));
+                skip_depth = 1;
+                continue;
+            }
+            Event::Html(_) | Event::InlineHtml(_) if !filter.raw_html => {
+                edits.push((range, ""));
+                continue;
+            }
+            Event::Start(Tag::Link { .. }) if !filter.links => {
+                links.push((range, None));
+                continue;
+            }
+            Event::End(TagEnd::Link) if !filter.links => {
+                // Keep the link text, dropping the brackets and target around it
+                if let Some((link, text)) = links.pop() {
+                    match text {
+                        Some(text) => {
+                            edits.push((link.start..text.start, ""));
+                            edits.push((text.end..link.end, ""));
+                        }
+                        None => edits.push((link, "")),
+                    }
+                    extend_link_text(&mut links, range);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        extend_link_text(&mut links, range);
+    }
+
+    edits.sort_by_key(|(range, _)| range.start);
+    let mut filtered = String::with_capacity(markdown.len());
+    let mut position = 0;
+    for (range, replacement) in edits {
+        if range.start < position {
+            continue;
+        }
+        filtered.push_str(&markdown[position..range.start]);
+        filtered.push_str(replacement);
+        position = range.end;
+    }
+    filtered.push_str(&markdown[position..]);
+    Cow::Owned(filtered)
+}
+
+// Grows the text span of the innermost open link to cover `range`
+fn extend_link_text(links: &mut [(Range<usize>, Option<Range<usize>>)], range: Range<usize>) {
+    if let Some((_, text)) = links.last_mut() {
+        *text = Some(match text.take() {
+            Some(text) => text.start.min(range.start)..text.end.max(range.end),
+            None => range,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn only(images: bool, links: bool, raw_html: bool) -> ViewerFilter {
+        ViewerFilter { images, links, raw_html }
+    }
+
+    #[test]
+    fn allowing_everything_borrows_the_input() {
+        let markdown = "![a](a.png) [b](https://b.com) <br>";
+        assert!(matches!(filter_markdown(markdown, ViewerFilter::default()), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn image_inside_a_link() {
+        let markdown = "See [![logo](logo.png) home](https://example.com) now";
+        assert_eq!(filter_markdown(markdown, only(false, true, true)), "See [ home](https://example.com) now");
+        assert_eq!(filter_markdown(markdown, only(true, false, true)), "See ![logo](logo.png) home now");
+        assert_eq!(filter_markdown(markdown, ViewerFilter::SAFE), "See  home now");
+    }
+
+    #[test]
+    fn link_with_inline_html() {
+        let markdown = "A [<b>bold</b> link](https://example.com).";
+        assert_eq!(filter_markdown(markdown, only(true, false, true)), "A <b>bold</b> link.");
+        assert_eq!(filter_markdown(markdown, only(true, true, false)), "A [bold link](https://example.com).");
+        assert_eq!(filter_markdown(markdown, ViewerFilter::SAFE), "A bold link.");
+    }
+
+    #[test]
+    fn html_block_is_removed_whole() {
+        // The blank line after the block stays, which renders the same
+        let markdown = "Before\n\n<div class=\"ad\">\n<p>Buy</p>\n</div>\n\nAfter";
+        assert_eq!(filter_markdown(markdown, only(true, true, false)), "Before\n\n\nAfter");
+    }
+
+    #[test]
+    fn safe_mode_over_a_mixed_document() {
+        let markdown = "# Title\n\n\
+            ![hero](hero.png)\n\n\
+            Read the [docs](https://example.com/docs) or `[code](kept)`.\n\n\
+            <script>alert(1)</script>\n\n\
+            - item with ![icon](i.png) and [a link](/a)\n\n\
+            | Col |\n| --- |\n| [cell](/c) |\n";
+        assert_eq!(
+            filter_markdown(markdown, ViewerFilter::SAFE),
+            "# Title\n\n\n\n\
+            Read the docs or `[code](kept)`.\n\n\n\
+            - item with  and a link\n\n\
+            | Col |\n| --- |\n| cell |\n"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use egui_extras::{Column, TableBuilder};
+use std::cmp::Ordering;
+
+/// A GFM table pulled out of scraped Markdown.
+pub struct MarkdownTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Finds the GFM tables in `markdown`, skipping fenced code blocks.
+/// Rows are padded or truncated to the header's column count.
+pub fn extract_tables(markdown: &str) -> Vec<MarkdownTable> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut tables = Vec::new();
+    let mut in_fence = false;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i].trim();
+        if line.starts_with("```") || line.starts_with("~~~") {
+            in_fence = !in_fence;
+            i += 1;
+            continue;
+        }
+        let starts_table = !in_fence
+            && line.contains('|')
+            && lines.get(i + 1).is_some_and(|next| is_delimiter_row(next));
+        if !starts_table {
+            i += 1;
+            continue;
+        }
+
+        let headers = split_row(line);
+        let mut rows = Vec::new();
+        i += 2; // Header and delimiter rows
+        while let Some(row) = lines.get(i).map(|l| l.trim()).filter(|l| l.contains('|')) {
+            let mut cells = split_row(row);
+            cells.resize(headers.len(), String::new());
+            rows.push(cells);
+            i += 1;
+        }
+        tables.push(MarkdownTable { headers, rows });
+    }
+    tables
+}
+
+// `| --- | :---: | ---: |`, with or without the outer pipes
+fn is_delimiter_row(line: &str) -> bool {
+    let cells = split_row(line.trim());
+    !cells.is_empty()
+        && cells.iter().all(|cell| {
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            !dashes.is_empty() && dashes.chars().all(|c| c == '-')
+        })
+}
+
+// Splits a table row on unescaped pipes, dropping the optional outer ones.
+fn split_row(line: &str) -> Vec<String> {
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = if line.ends_with('|') && !line.ends_with("\\|") { &line[..line.len() - 1] } else { line };
+
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cell.push('|');
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            _ => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+/// Renders each table as a grid with sortable columns. Click a header to sort
+/// (again to reverse); cells are selectable and can be copied from their context menu.
+pub fn show_tables(ui: &mut egui::Ui, tables: &[MarkdownTable]) {
+    if tables.is_empty() {
+        ui.label("No tables found in this result.");
+        return;
+    }
+
+    for (index, table) in tables.iter().enumerate() {
+        if index > 0 {
+            ui.add_space(10.0);
+        }
+        ui.push_id(index, |ui| show_table(ui, table));
+    }
+}
+
+fn show_table(ui: &mut egui::Ui, table: &MarkdownTable) {
+    // Sort column and direction (true = ascending), kept in egui memory per table
+    let sort_id = ui.id().with("sort");
+    let mut sort: Option<(usize, bool)> = ui.data(|d| d.get_temp(sort_id)).flatten();
+
+    let mut order: Vec<usize> = (0..table.rows.len()).collect();
+    if let Some((column, ascending)) = sort {
+        order.sort_by(|&a, &b| {
+            let ordering = compare_cells(&table.rows[a][column], &table.rows[b][column]);
+            if ascending { ordering } else { ordering.reverse() }
+        });
+    }
+
+    TableBuilder::new(ui)
+        .striped(true)
+        .vscroll(false) // The surrounding content area already scrolls
+        .columns(Column::auto().at_least(40.0).resizable(true), table.headers.len())
+        .header(22.0, |mut header| {
+            for (column, title) in table.headers.iter().enumerate() {
+                header.col(|ui| {
+                    let arrow = match sort {
+                        Some((sorted, true)) if sorted == column => " ▲",
+                        Some((sorted, false)) if sorted == column => " ▼",
+                        _ => "",
+                    };
+                    if ui.button(egui::RichText::new(format!("{}{}", title, arrow)).strong()).clicked() {
+                        sort = match sort {
+                            Some((sorted, ascending)) if sorted == column => Some((column, !ascending)),
+                            _ => Some((column, true)),
+                        };
+                    }
+                });
+            }
+        })
+        .body(|mut body| {
+            for &row_index in &order {
+                body.row(20.0, |mut row| {
+                    for cell in &table.rows[row_index] {
+                        row.col(|ui| {
+                            ui.add(egui::Label::new(cell.as_str()).selectable(true))
+                                .context_menu(|ui| {
+                                    if ui.button("📋 Copy cell").clicked() {
+                                        ui.ctx().copy_text(cell.clone());
+                                        ui.close_menu();
+                                    }
+                                });
+                        });
+                    }
+                });
+            }
+        });
+
+    ui.data_mut(|d| d.insert_temp(sort_id, sort));
+}
+
+// Numbers (ignoring currency symbols, commas and `%`) sort numerically; everything else by text.
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (parse_number(a), parse_number(b)) {
+        (Some(x), Some(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+fn parse_number(cell: &str) -> Option<f64> {
+    let cleaned: String = cell
+        .chars()
+        .filter(|c| !matches!(c, ',' | '$' | '€' | '£' | '%' | ' '))
+        .collect();
+    cleaned.parse().ok()
+}
@@ -0,0 +1,285 @@
+// HTML to Markdown. The backend's readability pass converts the article it
+// picked with it, and the frontend's Local scraper a whole page body.
+
+use scraper::{ElementRef, Node, Selector};
+use url::Url;
+
+// Never rendered as content
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "iframe", "svg", "canvas", "button", "select", "input",
+    "textarea",
+];
+// Elements whose content is laid out as separate blocks
+const BLOCK_ELEMENTS: &[&str] = &[
+    "div", "section", "article", "main", "header", "footer", "nav", "aside", "form", "figure", "figcaption", "dl",
+    "dt", "dd", "details", "summary", "address",
+];
+
+/// How HTML is converted to Markdown. By default link and image URLs are kept
+/// as written and only non-content elements (scripts, styles, form controls
+/// and the like) are left out.
+#[derive(Clone, Copy)]
+pub struct Converter<'a> {
+    base: Option<&'a Url>,
+    skip: &'a dyn Fn(ElementRef<'_>) -> bool,
+}
+
+impl Default for Converter<'_> {
+    fn default() -> Self {
+        fn keep(_: ElementRef<'_>) -> bool {
+            false
+        }
+        Converter { base: None, skip: &keep }
+    }
+}
+
+impl<'a> Converter<'a> {
+    /// Resolves relative link and image URLs against `base`. In-page
+    /// `#fragment` links and unparsable URLs stay as they are.
+    pub fn resolving_against(self, base: &'a Url) -> Self {
+        Converter { base: Some(base), ..self }
+    }
+
+    /// Also leaves out the elements `skip` returns true for, with their content.
+    pub fn skipping(self, skip: &'a dyn Fn(ElementRef<'_>) -> bool) -> Self {
+        Converter { skip, ..self }
+    }
+
+    /// `element`'s content as Markdown blocks separated by blank lines.
+    pub fn block_markdown(&self, element: ElementRef<'_>) -> String {
+        let mut writer = MarkdownWriter { converter: *self, blocks: Vec::new(), line: String::new() };
+        writer.children(element);
+        writer.flush();
+        writer.blocks.join("\n\n")
+    }
+
+    // Converts an element's content to a single line of inline Markdown
+    fn inline_markdown(&self, element: ElementRef<'_>) -> String {
+        let mut writer = MarkdownWriter { converter: *self, blocks: Vec::new(), line: String::new() };
+        writer.children(element);
+        writer.flush();
+        writer.blocks.join(" ")
+    }
+
+    fn resolve(&self, reference: &str) -> String {
+        match self.base {
+            Some(base) if !reference.starts_with('#') => {
+                base.join(reference).map_or_else(|_| reference.to_string(), String::from)
+            }
+            _ => reference.to_string(),
+        }
+    }
+
+    fn list_markdown(&self, list: ElementRef<'_>, ordered: bool) -> String {
+        let mut items = Vec::new();
+        for item in list.children().filter_map(ElementRef::wrap).filter(|child| child.value().name() == "li") {
+            let marker = if ordered { format!("{}. ", items.len() + 1) } else { "- ".to_string() };
+            let indent = " ".repeat(marker.len());
+            let content = self.block_markdown(item);
+            let mut lines = content.lines();
+            let Some(first) = lines.next() else { continue };
+            let mut entry = format!("{}{}", marker, first);
+            for line in lines {
+                entry.push('\n');
+                if !line.is_empty() {
+                    entry.push_str(&indent);
+                    entry.push_str(line);
+                }
+            }
+            items.push(entry);
+        }
+        items.join("\n")
+    }
+
+    fn table_markdown(&self, table: ElementRef<'_>) -> String {
+        let rows = Selector::parse("tr").expect("valid selector");
+        let rows: Vec<Vec<String>> = table
+            .select(&rows)
+            .map(|row| {
+                row.children()
+                    .filter_map(ElementRef::wrap)
+                    .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+                    .map(|cell| self.inline_markdown(cell).replace('|', "\\|"))
+                    .collect()
+            })
+            .filter(|cells: &Vec<String>| !cells.is_empty())
+            .collect();
+        let Some(columns) = rows.iter().map(Vec::len).max() else {
+            return String::new();
+        };
+        let line = |cells: &[String]| {
+            let padded = (0..columns).map(|i| cells.get(i).map_or("", String::as_str));
+            format!("| {} |", padded.collect::<Vec<_>>().join(" | "))
+        };
+        let mut lines = vec![line(&rows[0]), format!("|{}", " --- |".repeat(columns))];
+        lines.extend(rows[1..].iter().map(|row| line(row)));
+        lines.join("\n")
+    }
+}
+
+struct MarkdownWriter<'a> {
+    converter: Converter<'a>,
+    blocks: Vec<String>,
+    line: String, // Inline content of the block being built
+}
+
+impl MarkdownWriter<'_> {
+    fn flush(&mut self) {
+        let text = self.line.trim();
+        if !text.is_empty() {
+            self.blocks.push(text.to_string());
+        }
+        self.line.clear();
+    }
+
+    fn push_block(&mut self, block: String) {
+        self.flush();
+        if !block.trim().is_empty() {
+            self.blocks.push(block);
+        }
+    }
+
+    // Appends text with runs of whitespace collapsed to one space
+    fn text(&mut self, text: &str) {
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if !self.line.is_empty() && !self.line.ends_with(' ') && !self.line.ends_with('\n') {
+                    self.line.push(' ');
+                }
+            } else {
+                self.line.push(c);
+            }
+        }
+    }
+
+    fn children(&mut self, element: ElementRef<'_>) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.text(text),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        self.element(child);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn element(&mut self, element: ElementRef<'_>) {
+        let name = element.value().name();
+        if SKIPPED_ELEMENTS.contains(&name) || (self.converter.skip)(element) {
+            return;
+        }
+        let converter = self.converter;
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse().unwrap_or(1);
+                let text = converter.inline_markdown(element);
+                if !text.is_empty() {
+                    self.push_block(format!("{} {}", "#".repeat(level), text));
+                }
+            }
+            "p" => {
+                self.flush();
+                self.children(element);
+                self.flush();
+            }
+            "br" => self.line.push_str("  \n"),
+            "hr" => self.push_block("---".to_string()),
+            "pre" => {
+                let code: String = element.text().collect();
+                self.push_block(format!("```\n{}\n```", code.trim_end_matches('\n')));
+            }
+            "blockquote" => {
+                let quoted = converter
+                    .block_markdown(element)
+                    .lines()
+                    .map(|line| if line.is_empty() { ">".to_string() } else { format!("> {}", line) })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                self.push_block(quoted);
+            }
+            "ul" | "ol" => self.push_block(converter.list_markdown(element, name == "ol")),
+            "table" => self.push_block(converter.table_markdown(element)),
+            "img" => {
+                // Inline data: images would bury the text
+                if let Some(src) = element.value().attr("src").filter(|src| !src.starts_with("data:")) {
+                    let alt = element.value().attr("alt").unwrap_or_default();
+                    self.line.push_str(&format!("![{}]({})", alt, converter.resolve(src)));
+                }
+            }
+            "a" => {
+                let text = converter.inline_markdown(element);
+                match element.value().attr("href").filter(|href| !href.starts_with("javascript:")) {
+                    Some(href) if !text.is_empty() => {
+                        self.line.push_str(&format!("[{}]({})", text, converter.resolve(href)))
+                    }
+                    _ => self.line.push_str(&text),
+                }
+            }
+            "strong" | "b" => self.wrapped(element, "**"),
+            "em" | "i" => self.wrapped(element, "*"),
+            "code" => {
+                let code: String = element.text().collect();
+                if !code.trim().is_empty() {
+                    self.line.push_str(&format!("`{}`", code.trim()));
+                }
+            }
+            _ if BLOCK_ELEMENTS.contains(&name) => {
+                self.flush();
+                self.children(element);
+                self.flush();
+            }
+            _ => self.children(element),
+        }
+    }
+
+    fn wrapped(&mut self, element: ElementRef<'_>, marker: &str) {
+        let text = self.converter.inline_markdown(element);
+        if !text.is_empty() {
+            self.line.push_str(&format!("{}{}{}", marker, text, marker));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::Html;
+
+    fn convert(html: &str, converter: Converter<'_>) -> String {
+        converter.block_markdown(Html::parse_fragment(html).root_element())
+    }
+
+    #[test]
+    fn tables_become_pipe_tables() {
+        let html = "<table><tr><th>Name</th><th>Size</th></tr><tr><td>a|b</td><td>2</td></tr></table>";
+        assert_eq!(convert(html, Converter::default()), "| Name | Size |\n| --- | --- |\n| a\\|b | 2 |");
+    }
+
+    #[test]
+    fn nested_lists_are_indented_under_their_item() {
+        let html = "<ul><li>one<ol><li>two</li><li>three</li></ol></li><li>four</li></ul>";
+        assert_eq!(convert(html, Converter::default()), "- one\n\n  1. two\n  2. three\n- four");
+    }
+
+    #[test]
+    fn data_images_are_skipped() {
+        let html = r#"<p>before <img src="data:image/png;base64,AAAA" alt="inline"> after</p>"#;
+        assert_eq!(convert(html, Converter::default()), "before after");
+    }
+
+    #[test]
+    fn urls_are_kept_as_written_without_a_base() {
+        let html = r#"<p><a href="/about">About</a> <img src="logo.png" alt="Logo"></p>"#;
+        assert_eq!(convert(html, Converter::default()), "[About](/about) ![Logo](logo.png)");
+    }
+
+    #[test]
+    fn skipped_elements_are_left_out_with_their_content() {
+        let html = r#"<h1>Title</h1><div class="share">Share this</div><p>Body text</p>"#;
+        let skip = |element: ElementRef<'_>| element.value().attr("class") == Some("share");
+        assert_eq!(convert(html, Converter::default().skipping(&skip)), "# Title\n\nBody text");
+    }
+}
@@ -0,0 +1,31 @@
+// Markdown helpers shared by the backend and the frontend.
+
+pub mod html;
+mod wrap;
+
+pub use wrap::wrap;
+
+/// Given `open_at` pointing at an opening delimiter, returns the index of its
+/// matching closing delimiter, honouring nesting. Stops at blank lines.
+pub fn matching_close(text: &str, open_at: usize, open: char, close: char) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut prev_newline = false;
+    for (i, c) in text[open_at..].char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(open_at + i);
+            }
+        } else if c == '\n' {
+            if prev_newline {
+                return None;
+            }
+            prev_newline = true;
+            continue;
+        }
+        prev_newline = false;
+    }
+    None
+}
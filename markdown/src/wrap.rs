@@ -0,0 +1,181 @@
+// Hard-wrapping of Markdown: the backend's `wrap_width` option and the
+// frontend's wrapped exports.
+
+use crate::matching_close;
+
+/// Hard-wraps prose lines longer than `width` at word boundaries. Fenced and
+/// indented code, tables, headings and HTML are left alone, and links, images,
+/// inline code and autolinks are never split. Continuation lines keep the
+/// line's list/blockquote indentation.
+pub fn wrap(markdown: &str, width: usize) -> String {
+    let mut out = String::with_capacity(markdown.len() + markdown.len() / width.max(1));
+    let mut in_fence = false;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        if in_fence || line.chars().count() <= width || !is_wrappable(line) {
+            out.push_str(line);
+        } else {
+            wrap_line(line, width, &mut out);
+        }
+        out.push('\n');
+    }
+    if !markdown.ends_with('\n') {
+        out.pop();
+    }
+    out
+}
+
+// Lines whose meaning would change if split across lines
+fn is_wrappable(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    !(line.starts_with("    ")
+        || line.starts_with('\t')
+        || trimmed.starts_with('#')
+        || trimmed.starts_with('|')
+        || trimmed.starts_with('<')
+        || (trimmed.starts_with('[') && trimmed.contains("]:"))) // Link reference definition
+}
+
+fn wrap_line(line: &str, width: usize, out: &mut String) {
+    let (prefix, body) = split_prefix(line);
+    // Continuation lines line up under the text, keeping blockquote markers
+    let continuation: String = prefix
+        .chars()
+        .map(|c| if c == '>' || c.is_whitespace() { c } else { ' ' })
+        .collect();
+    let hard_break = body.ends_with("  ");
+
+    let mut current = prefix.to_string();
+    let mut current_len = current.chars().count();
+    let mut line_has_words = false;
+    for token in tokens(body) {
+        let token_len = token.chars().count();
+        if line_has_words && current_len + 1 + token_len > width {
+            out.push_str(&current);
+            out.push('\n');
+            current = continuation.clone();
+            current_len = current.chars().count();
+            line_has_words = false;
+        }
+        if line_has_words {
+            current.push(' ');
+            current_len += 1;
+        }
+        current.push_str(token);
+        current_len += token_len;
+        line_has_words = true;
+    }
+    out.push_str(&current);
+    if hard_break {
+        out.push_str("  ");
+    }
+}
+
+// Splits off leading indentation, blockquote markers and a list marker.
+fn split_prefix(line: &str) -> (&str, &str) {
+    let mut end = 0;
+    loop {
+        let rest = &line[end..];
+        let spaces = rest.len() - rest.trim_start().len();
+        let rest_trimmed = &rest[spaces..];
+        if rest_trimmed.starts_with('>') {
+            end += spaces + 1;
+        } else if let Some(marker_len) = list_marker_len(rest_trimmed) {
+            return (&line[..end + spaces + marker_len], &line[end + spaces + marker_len..]);
+        } else {
+            return (&line[..end + spaces], &line[end + spaces..]);
+        }
+    }
+}
+
+// Length of a `- `, `* `, `+ ` or `12. ` marker (including the space), if present.
+fn list_marker_len(text: &str) -> Option<usize> {
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    let marker = if digits > 0 && text[digits..].starts_with(['.', ')']) {
+        digits + 1
+    } else if text.starts_with(['-', '*', '+']) {
+        1
+    } else {
+        return None;
+    };
+    text[marker..].starts_with(' ').then_some(marker + 1)
+}
+
+// Splits on whitespace, keeping links, images, code spans and autolinks whole.
+fn tokens(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        let c = rest.chars().next().unwrap();
+        if c.is_whitespace() {
+            if let Some(s) = start.take() {
+                tokens.push(&text[s..i]);
+            }
+            i += c.len_utf8();
+            continue;
+        }
+        start.get_or_insert(i);
+        i += atom_len(rest).unwrap_or(c.len_utf8());
+    }
+    if let Some(s) = start {
+        tokens.push(&text[s..]);
+    }
+    tokens
+}
+
+// Byte length of an unbreakable construct at the start of `text`, if any.
+fn atom_len(text: &str) -> Option<usize> {
+    if text.starts_with('`') {
+        let ticks = text.chars().take_while(|&c| c == '`').count();
+        let fence = &text[..ticks];
+        return text[ticks..].find(fence).map(|end| ticks + end + ticks);
+    }
+    if text.starts_with('<') {
+        let end = text.find('>')?;
+        return (!text[1..end].contains(char::is_whitespace)).then_some(end + 1);
+    }
+    let offset = usize::from(text.starts_with("!["));
+    if text[offset..].starts_with('[') {
+        let label_end = matching_close(text, offset, '[', ']')?;
+        return match text[label_end + 1..].chars().next() {
+            Some('(') => matching_close(text, label_end + 1, '(', ')').map(|end| end + 1),
+            Some('[') => matching_close(text, label_end + 1, '[', ']').map(|end| end + 1),
+            _ => Some(label_end + 1),
+        };
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fenced_code_is_never_wrapped() {
+        let markdown = "```\nlet value = some_function(first_argument, second_argument, third_argument);\n```\n";
+        assert_eq!(wrap(markdown, 20), markdown);
+    }
+
+    #[test]
+    fn a_link_longer_than_the_width_stays_on_one_line() {
+        let markdown = "see [a link with many words](https://example.com/a/very/long/path) here";
+        assert_eq!(wrap(markdown, 20), "see\n[a link with many words](https://example.com/a/very/long/path)\nhere");
+    }
+
+    #[test]
+    fn wrapped_list_items_continue_under_their_text() {
+        assert_eq!(wrap("- one two three four five six", 12), "- one two\n  three four\n  five six");
+        assert_eq!(wrap("  12. one two three four", 16), "  12. one two\n      three four");
+    }
+
+    #[test]
+    fn blockquote_markers_are_repeated_on_continuation_lines() {
+        assert_eq!(wrap("> one two three four", 12), "> one two\n> three four");
+    }
+}
@@ -4,6 +4,17 @@ use chrono::{DateTime, Utc};
 #[derive(Deserialize)]
 pub struct ScrapeRequest {
     pub url: String,
+    /// Optional summarization style, e.g. "5 bullet points" or "focus on pricing".
+    /// Accepted as `instructions` or `prompt`; the default summary is used when absent.
+    #[serde(default, alias = "prompt")]
+    pub instructions: Option<String>,
+}
+
+impl ScrapeRequest {
+    /// Trimmed instructions, treating an empty string the same as none
+    pub fn instructions(&self) -> Option<&str> {
+        self.instructions.as_deref().map(str::trim).filter(|s| !s.is_empty())
+    }
 }
 
 #[derive(Serialize)]
@@ -83,8 +83,11 @@ async fn scrape_handler(
 }
 
 async fn process_scrape_request(state: &AppState, req: &ScrapeRequest) -> Result<ScrapeResponse> {
-    // Check cache first
-    let cache_key = &req.url;
+    // Check cache first; summaries written with different instructions are cached separately
+    let cache_key = &match req.instructions() {
+        Some(instructions) => format!("{}\n{}", req.url, instructions),
+        None => req.url.clone(),
+    };
     
     // Try to get from cache with lock scope to minimize lock contention
     {
@@ -143,7 +146,7 @@ async fn process_scrape_request(state: &AppState, req: &ScrapeRequest) -> Result
     let formatted = format_html(&raw_body);
     println!("Content size: {} chars (using full content)", formatted.len());
     
-    let prompt = build_prompt(&formatted);
+    let prompt = build_prompt(&formatted, req.instructions());
     println!("Built prompt with length: {} chars", prompt.len());
 
     // Calculate word count
@@ -183,7 +186,7 @@ async fn process_scrape_request(state: &AppState, req: &ScrapeRequest) -> Result
     // Store in cache
     {
         let mut cache = state.cache.lock().unwrap();
-        cache.insert(req.url.clone(), CachedResponse {
+        cache.insert(cache_key.clone(), CachedResponse {
             summary: final_summary.clone(),
             word_count,
             timestamp: Utc::now(),
@@ -148,10 +148,17 @@ fn clean_html_content(html: &str) -> String {
     normalized
 }
 
-pub fn build_prompt(content: &str) -> String {
+pub fn build_prompt(content: &str, instructions: Option<&str>) -> String {
     // Use a more efficient string format that pre-allocates approximately the right amount of space
-    let mut result = String::with_capacity(content.len() + 150);
-    result.push_str("The following is the content of a webpage. Please provide a concise summary formatted in Markdown. Focus on the key points, main ideas, and important details. Use headers, bullet points, and other Markdown formatting to make the summary structured and readable:\n\n");
+    let mut result = String::with_capacity(content.len() + 150 + instructions.map_or(0, str::len));
+    match instructions {
+        Some(instructions) => {
+            result.push_str("The following is the content of a webpage. Summarize it in Markdown, following these instructions: ");
+            result.push_str(instructions);
+            result.push_str("\n\n");
+        }
+        None => result.push_str("The following is the content of a webpage. Please provide a concise summary formatted in Markdown. Focus on the key points, main ideas, and important details. Use headers, bullet points, and other Markdown formatting to make the summary structured and readable:\n\n"),
+    }
     result.push_str(content);
     result
 } 
\ No newline at end of file